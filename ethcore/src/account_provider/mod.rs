@@ -50,6 +50,16 @@ enum Unlock {
 	Timed(Instant),
 }
 
+impl Unlock {
+	/// Whether the timeout of a timed unlock has passed.
+	fn is_expired(&self) -> bool {
+		match *self {
+			Unlock::Timed(ref end) => Instant::now() > *end,
+			_ => false,
+		}
+	}
+}
+
 /// Data associated with account.
 #[derive(Clone)]
 struct AccountData {
@@ -412,6 +422,8 @@ impl AccountProvider {
 
 		// check if account is already unlocked permanently, if it is, do nothing
 		let mut unlocked = self.unlocked.write();
+		// don't keep passwords of expired unlocks around until their next use
+		unlocked.retain(|_, data| !data.unlock.is_expired());
 		if let Some(data) = unlocked.get(&account) {
 			if let Unlock::Perm = data.unlock {
 				return Ok(())
@@ -443,11 +455,9 @@ impl AccountProvider {
 		if let Unlock::OneTime = data.unlock {
 			unlocked.remove(account).expect("data exists: so key must exist: qed");
 		}
-		if let Unlock::Timed(ref end) = data.unlock {
-			if Instant::now() > *end {
-				unlocked.remove(account).expect("data exists: so key must exist: qed");
-				return Err(SignError::NotUnlocked);
-			}
+		if data.unlock.is_expired() {
+			unlocked.remove(account).expect("data exists: so key must exist: qed");
+			return Err(SignError::NotUnlocked);
 		}
 		Ok(data.password)
 	}
@@ -472,7 +482,7 @@ impl AccountProvider {
 		let unlocked = self.unlocked.read();
 		let unlocked_secrets = self.unlocked_secrets.read();
 		self.sstore.account_ref(address)
-			.map(|r| unlocked.get(&r).is_some() || unlocked_secrets.get(&r).is_some())
+			.map(|r| unlocked.get(&r).map_or(false, |data| !data.unlock.is_expired()) || unlocked_secrets.get(&r).is_some())
 			.unwrap_or(false)
 	}
 
@@ -776,9 +786,26 @@ mod tests {
 		assert!(ap.unlock_account_timed(kp.address(), "test".into(), Duration::from_secs(60)).is_ok());
 		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
 		ap.unlocked.write().get_mut(&StoreAccountRef::root(kp.address())).unwrap().unlock = Unlock::Timed(Instant::now());
+		assert!(!ap.is_unlocked(&kp.address()));
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
 
+	#[test]
+	fn unlocking_forgets_expired_unlocks() {
+		let kp1 = Random.generate().unwrap();
+		let kp2 = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp1.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.insert_account(kp2.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_account_timed(kp1.address(), "test".into(), Duration::from_secs(60)).is_ok());
+		ap.unlocked.write().get_mut(&StoreAccountRef::root(kp1.address())).unwrap().unlock = Unlock::Timed(Instant::now());
+
+		assert!(ap.unlock_account_timed(kp2.address(), "test".into(), Duration::from_secs(60)).is_ok());
+
+		assert!(!ap.unlocked.read().contains_key(&StoreAccountRef::root(kp1.address())));
+		assert!(ap.sign(kp2.address(), None, Default::default()).is_ok());
+	}
+
 	#[test]
 	fn should_sign_and_return_token() {
 		// given
@@ -17,7 +17,7 @@
 //! Blockchain database.
 
 use std::collections::{HashMap, HashSet};
-use std::{mem, io};
+use std::{cmp, mem, io};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -30,7 +30,7 @@ use blockchain::update::{ExtrasUpdate, ExtrasInsert};
 use blooms_db;
 use bytes::Bytes;
 use cache_manager::CacheManager;
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use encoded;
 use engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
 use engines::ForkChoice;
@@ -60,6 +60,11 @@ pub trait BlockChainDB: Send + Sync {
 
 	/// Trace blooms database.
 	fn trace_blooms(&self) -> &blooms_db::Database;
+
+	/// I/O counters of the key value store, if it keeps any.
+	fn io_stats(&self) -> db::IoStats {
+		db::IoStats::default()
+	}
 }
 
 /// Generic database handler. This trait contains one function `open`. When called, it opens database with a
@@ -108,6 +113,28 @@ pub trait BlockProvider {
 	/// Get the hash of given block's number.
 	fn block_hash(&self, index: BlockNumber) -> Option<H256>;
 
+	/// Get the hashes of up to `max` canonical blocks, starting at `start` and stepping over
+	/// `skip` blocks between each, towards genesis if `reverse` is set.
+	///
+	/// The result is truncated at genesis, or at the first block whose hash isn't known.
+	fn block_hashes_with_skip(&self, start: BlockNumber, max: usize, skip: u64, reverse: bool) -> Vec<H256> {
+		let step = skip.saturating_add(1);
+		let mut hashes = Vec::with_capacity(max);
+		let mut number = start;
+		while hashes.len() < max {
+			match self.block_hash(number) {
+				Some(hash) => hashes.push(hash),
+				None => break,
+			}
+			number = match reverse {
+				true if number < step => break,
+				true => number - step,
+				false => number.saturating_add(step),
+			};
+		}
+		hashes
+	}
+
 	/// Get the address of transaction with given hash.
 	fn transaction_address(&self, hash: &H256) -> Option<TransactionAddress>;
 
@@ -324,6 +351,47 @@ impl BlockProvider for BlockChain {
 		Some(result)
 	}
 
+	/// Get the hashes of up to `max` canonical blocks, starting at `start` and stepping over
+	/// `skip` blocks between each, towards genesis if `reverse` is set.
+	///
+	/// The result is truncated at genesis, at the best block, or at the first block whose hash
+	/// isn't known.
+	fn block_hashes_with_skip(&self, start: BlockNumber, max: usize, skip: u64, reverse: bool) -> Vec<H256> {
+		if max == 0 {
+			return Vec::new();
+		}
+
+		let step = skip.saturating_add(1);
+		let span = step.saturating_mul(max as u64 - 1);
+		if skip == 0 && !reverse {
+			return self.block_hashes_in_range(start, start.saturating_add(span));
+		}
+		if skip == 0 {
+			let from = start.saturating_sub(span);
+			let mut hashes = self.block_hashes_in_range(from, start);
+			// going down from `start`, only a complete range can be used as is.
+			if hashes.len() as u64 == start - from + 1 {
+				hashes.reverse();
+				return hashes;
+			}
+		}
+
+		let mut hashes = Vec::with_capacity(max);
+		let mut number = start;
+		while hashes.len() < max && number <= self.best_block_number() {
+			match self.block_hash(number) {
+				Some(hash) => hashes.push(hash),
+				None => break,
+			}
+			number = match reverse {
+				true if number < step => break,
+				true => number - step,
+				false => number.saturating_add(step),
+			};
+		}
+		hashes
+	}
+
 	/// Get the address of transaction with given hash.
 	fn transaction_address(&self, hash: &H256) -> Option<TransactionAddress> {
 		let result = self.db.key_value().read_with_cache(db::COL_EXTRA, &self.transaction_addresses, hash)?;
@@ -1356,6 +1424,47 @@ impl BlockChain {
 		self.best_block.read().header.hash()
 	}
 
+	/// Get the hashes of the canonical blocks numbered `from` to `to` inclusive.
+	///
+	/// The result is truncated at the best block, or at the first block whose hash isn't known
+	/// (e.g. one below the ancient blocks imported so far).
+	pub fn block_hashes_in_range(&self, from: BlockNumber, to: BlockNumber) -> Vec<H256> {
+		let to = cmp::min(to, self.best_block_number());
+		if from > to {
+			return Vec::new();
+		}
+
+		let mut hashes = Vec::with_capacity((to - from + 1) as usize);
+		let mut start = from;
+		// the number index is big-endian, each run of 256 numbers shares all but the last key byte.
+		while start <= to {
+			let end = cmp::min(to, start | 0xff);
+			let key = <BlockNumber as Key<H256>>::key(&start);
+			let prefix = &key[..4];
+			// the iterator runs past the prefix, see `EpochTransitionIter`.
+			let stored: HashMap<u8, H256> = self.db.key_value().iter_from_prefix(db::COL_EXTRA, prefix)
+				.take_while(|&(ref key, _)| key.starts_with(prefix))
+				.filter(|&(ref key, _)| key.len() == 5)
+				.map(|(key, value)| (key[4], ::rlp::decode(&value).expect("decode db value failed")))
+				.collect();
+
+			// recently written entries may not be iterable yet, but are always cached.
+			let cached: Vec<_> = {
+				let cache = self.block_hashes.read();
+				(start..end + 1).map(|number| cache.get(&number).cloned()).collect()
+			};
+			for (number, cached) in (start..end + 1).zip(cached) {
+				// anything else missing from the iteration is read on its own.
+				match cached.or_else(|| stored.get(&(number as u8)).cloned()).or_else(|| self.block_hash(number)) {
+					Some(hash) => hashes.push(hash),
+					None => return hashes,
+				}
+			}
+			start = end + 1;
+		}
+		hashes
+	}
+
 	/// Get best block number.
 	pub fn best_block_number(&self) -> BlockNumber {
 		self.best_block.read().header.number()
@@ -1926,6 +2035,95 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn returns_block_hashes_in_range() {
+		let genesis = BlockBuilder::genesis();
+		let generator = BlockGenerator::new(vec![genesis.add_blocks(299)]);
+
+		let db = new_db();
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+
+		let mut block_hashes = vec![genesis.last().hash()];
+		let mut batch = db.key_value().transaction();
+		for block in generator {
+			block_hashes.push(block.hash());
+			insert_block_batch(&mut batch, &bc, block.encoded(), vec![]);
+			bc.commit();
+		}
+		db.key_value().write(batch).unwrap();
+
+		// the second chain reads everything from the database instead of the cache.
+		let reopened = new_chain(genesis.last().encoded(), db.clone());
+		for bc in &[bc, reopened] {
+			assert_eq!(bc.block_hashes_in_range(0, 299), block_hashes);
+			assert_eq!(bc.block_hashes_in_range(250, 1000), &block_hashes[250..]);
+			assert_eq!(bc.block_hashes_in_range(7, 7), vec![block_hashes[7]]);
+			assert!(bc.block_hashes_in_range(8, 7).is_empty());
+			assert!(bc.block_hashes_in_range(300, 400).is_empty());
+
+			assert_eq!(bc.block_hashes_with_skip(10, 3, 0, false), &block_hashes[10..13]);
+			assert_eq!(bc.block_hashes_with_skip(10, 5, 2, false), vec![10, 13, 16, 19, 22].into_iter().map(|n| block_hashes[n]).collect::<Vec<_>>());
+			assert_eq!(bc.block_hashes_with_skip(10, 5, 2, true), vec![10, 7, 4, 1].into_iter().map(|n| block_hashes[n]).collect::<Vec<_>>());
+			assert_eq!(bc.block_hashes_with_skip(2, 5, 0, true), vec![block_hashes[2], block_hashes[1], block_hashes[0]]);
+			assert_eq!(bc.block_hashes_with_skip(298, 5, 0, false), &block_hashes[298..]);
+			assert!(bc.block_hashes_with_skip(300, 5, 0, true).is_empty());
+			assert!(bc.block_hashes_with_skip(10, 0, 0, false).is_empty());
+		}
+	}
+
+	#[test]
+	fn block_hashes_in_range_read_unflushed_entries() {
+		use blockchain::BlockChainDBHandler;
+		use db::NUM_COLUMNS;
+		use kvdb_rocksdb::DatabaseConfig;
+		use tempdir::TempDir;
+		use test_helpers::restoration_db_handler;
+
+		let genesis = BlockBuilder::genesis();
+		let generator = BlockGenerator::new(vec![genesis.add_blocks(20)]);
+
+		let tempdir = TempDir::new("").unwrap();
+		let db = restoration_db_handler(DatabaseConfig::with_columns(NUM_COLUMNS)).open(tempdir.path()).unwrap();
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+
+		let mut block_hashes = vec![genesis.last().hash()];
+		let mut batch = db.key_value().transaction();
+		for block in generator {
+			block_hashes.push(block.hash());
+			insert_block_batch(&mut batch, &bc, block.encoded(), vec![]);
+			bc.commit();
+		}
+		// buffered writes can be read, but aren't iterated over until they are flushed.
+		db.key_value().write_buffered(batch);
+
+		let reopened = new_chain(genesis.last().encoded(), db.clone());
+		assert_eq!(reopened.best_block_number(), 20);
+		assert_eq!(reopened.block_hashes_in_range(0, 20), block_hashes);
+		assert_eq!(reopened.block_hashes_with_skip(20, 3, 0, true), vec![block_hashes[20], block_hashes[19], block_hashes[18]]);
+	}
+
+	#[test]
+	fn block_hashes_in_range_follow_reorgs() {
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block();
+		let b2 = b1.add_block();
+		let b3a = b2.add_block_with_difficulty(20);
+		let b3b = b2.add_block_with_difficulty(9);
+		let b4b = b3b.add_block_with_difficulty(9);
+
+		let db = new_db();
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		for block in &[&b1, &b2, &b3b, &b4b] {
+			insert_block(&db, &bc, block.last().encoded(), vec![]);
+		}
+		assert_eq!(bc.block_hashes_in_range(2, 4), vec![b2.last().hash(), b3b.last().hash(), b4b.last().hash()]);
+
+		// b3a makes a shorter but heavier chain, the stale hash of block 4 must not be returned.
+		insert_block(&db, &bc, b3a.last().encoded(), vec![]);
+		assert_eq!(bc.best_block_hash(), b3a.last().hash());
+		assert_eq!(bc.block_hashes_in_range(2, 10), vec![b2.last().hash(), b3a.last().hash()]);
+	}
+
 	#[test]
 	fn can_contain_arbitrary_block_sequence() {
 		let bc = generate_dummy_blockchain(50);
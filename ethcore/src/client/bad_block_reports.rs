@@ -0,0 +1,188 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reports describing how our execution of a block diverged from its header.
+//!
+//! When a block fails final verification, the state we computed for it only lives in the
+//! journal overlay of the `StateDB` it was enacted on, which is never committed. A report is
+//! built by diffing that post-state against the parent state before the block is dropped.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use block::ExecutedBlock;
+use client::state_changes;
+use ethereum_types::{H256, U256};
+use ethtrie;
+use factory::Factories;
+use hashdb::HashDB;
+use header::Header;
+use keccak_hasher::KeccakHasher;
+use receipt::TransactionOutcome;
+use rustc_hex::ToHex;
+use serde_json::{self, Value};
+use types::basic_account::BasicAccount;
+
+/// Write a report for `block`, which was enacted on top of `parent` but doesn't match
+/// `expected`, into the `dir` directory. Returns the path of the written report.
+pub fn write(
+	dir: &Path,
+	expected: &Header,
+	parent: &Header,
+	block: ExecutedBlock,
+	factories: &Factories,
+	error: String,
+) -> io::Result<PathBuf> {
+	let transactions = block.transactions.iter().zip(block.receipts.iter()).scan(U256::zero(), |cumulative, (tx, receipt)| {
+		let gas_used = receipt.gas_used - *cumulative;
+		*cumulative = receipt.gas_used;
+		let outcome = match receipt.outcome {
+			TransactionOutcome::Unknown => Value::Null,
+			TransactionOutcome::StateRoot(root) => json!({ "stateRoot": hex_hash(&root) }),
+			TransactionOutcome::StatusCode(status) => json!({ "status": status }),
+		};
+		let logs = receipt.logs.iter().map(|log| json!({
+			"address": format!("0x{:x}", log.address),
+			"topics": log.topics.iter().map(hex_hash).collect::<Vec<_>>(),
+			"data": format!("0x{}", log.data.to_hex()),
+		})).collect::<Vec<_>>();
+
+		Some(json!({
+			"hash": hex_hash(&tx.hash()),
+			"gasUsed": hex_u256(&gas_used),
+			"cumulativeGasUsed": hex_u256(&receipt.gas_used),
+			"outcome": outcome,
+			"logs": logs,
+		}))
+	}).collect::<Vec<_>>();
+
+	let computed = block.header.clone();
+	let (post_root, db) = block.state.drop();
+	let (accounts, accounts_error) = match account_changes(db.as_hashdb(), factories, parent.state_root(), &post_root) {
+		Ok(accounts) => (accounts, Value::Null),
+		Err(e) => (Vec::new(), Value::String(format!("{}", e))),
+	};
+
+	let report = json!({
+		"number": expected.number(),
+		"hash": hex_hash(&expected.hash()),
+		"parentHash": hex_hash(expected.parent_hash()),
+		"error": error,
+		"expected": summary(expected),
+		"computed": summary(&computed),
+		"transactions": transactions,
+		"accounts": accounts,
+		"accountsError": accounts_error,
+	});
+
+	fs::create_dir_all(dir)?;
+	let path = dir.join(format!("{}-{:x}.json", expected.number(), expected.hash()));
+	let mut file = File::create(&path)?;
+	serde_json::to_writer_pretty(&mut file, &report).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+	file.write_all(b"\n")?;
+	Ok(path)
+}
+
+fn summary(header: &Header) -> Value {
+	json!({
+		"stateRoot": hex_hash(header.state_root()),
+		"receiptsRoot": hex_hash(header.receipts_root()),
+		"gasUsed": hex_u256(header.gas_used()),
+	})
+}
+
+/// Describe the accounts and storage slots which differ between the states with roots
+/// `pre_root` and `post_root`. Addresses and slots are keyed by their hashes, the preimages
+/// are only known in a fat database.
+fn account_changes(db: &HashDB<KeccakHasher>, factories: &Factories, pre_root: &H256, post_root: &H256) -> ethtrie::Result<Vec<Value>> {
+	let changes = state_changes::account_changes(db, factories, pre_root, post_root)?;
+
+	Ok(changes.into_iter().map(|change| {
+		let account_db = factories.accountdb.readonly(db, change.address_hash);
+		let value = |value: Option<U256>| value.map_or(Value::Null, |v| Value::String(hex_u256(&v)));
+		let storage = change.storage.iter().map(|slot| json!({
+			"keyHash": hex_hash(&slot.key_hash),
+			"key": hex_preimage(&*account_db, &slot.key_hash),
+			"pre": value(slot.pre),
+			"post": value(slot.post),
+		})).collect::<Vec<_>>();
+
+		json!({
+			"addressHash": hex_hash(&change.address_hash),
+			"address": hex_preimage(db, &change.address_hash),
+			"pre": change.pre.as_ref().map(account),
+			"post": change.post.as_ref().map(account),
+			"storage": storage,
+		})
+	}).collect())
+}
+
+fn account(account: &BasicAccount) -> Value {
+	json!({
+		"balance": hex_u256(&account.balance),
+		"nonce": hex_u256(&account.nonce),
+		"codeHash": hex_hash(&account.code_hash),
+		"storageRoot": hex_hash(&account.storage_root),
+	})
+}
+
+/// The key hashed into `hash` as hex, if the trie is a fat one.
+fn hex_preimage(db: &HashDB<KeccakHasher>, hash: &H256) -> Option<String> {
+	state_changes::preimage(db, hash).map(|key| format!("0x{}", key.to_hex()))
+}
+
+fn hex_hash(hash: &H256) -> String {
+	format!("0x{:x}", hash)
+}
+
+fn hex_u256(value: &U256) -> String {
+	format!("0x{:x}", value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::Address;
+	use hash::keccak;
+	use state::CleanupMode;
+	use test_helpers::get_temp_state;
+
+	#[test]
+	fn reports_changed_accounts_and_slots() {
+		let mut state = get_temp_state();
+		let a = Address::from(1);
+		state.add_balance(&a, &100.into(), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+		let pre_root = *state.root();
+
+		state.add_balance(&a, &23.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, H256::from(2), H256::from(7)).unwrap();
+		state.commit().unwrap();
+		let (post_root, db) = state.drop();
+
+		let accounts = account_changes(db.as_hashdb(), &Factories::default(), &pre_root, &post_root).unwrap();
+		assert_eq!(accounts.len(), 1);
+		let account = &accounts[0];
+		assert_eq!(account["addressHash"], json!(hex_hash(&keccak(&a))));
+		assert_eq!(account["address"], Value::Null);
+		assert_eq!(account["pre"]["balance"], json!("0x64"));
+		assert_eq!(account["post"]["balance"], json!("0x7b"));
+		assert_eq!(account["storage"][0]["keyHash"], json!(hex_hash(&keccak(&H256::from(2)))));
+		assert_eq!(account["storage"][0]["pre"], Value::Null);
+		assert_eq!(account["storage"][0]["post"], json!("0x7"));
+	}
+}
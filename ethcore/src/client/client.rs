@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashSet, BTreeMap, VecDeque};
+use std::collections::{HashMap, HashSet, BTreeMap, VecDeque};
 use std::cmp;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
@@ -22,11 +22,14 @@ use std::sync::{Arc, Weak};
 use std::time::{Instant, Duration};
 
 // util
-use hash::keccak;
+use hash::{keccak, KECCAK_EMPTY};
 use bytes::Bytes;
 use itertools::Itertools;
 use journaldb;
-use trie::{TrieSpec, TrieFactory, Trie};
+use trie::{TrieSpec, TrieFactory, TrieIterator, TrieError};
+use ethtrie::{self, FatDB, FatItemIterator, Direction};
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
 use kvdb::{DBValue, KeyValueDB, DBTransaction};
 
 // other
@@ -47,7 +50,8 @@ use client::{
 	ChainNotify, ChainRoute, PruningInfo, ProvingBlockChainClient, EngineInfo, ChainMessageType,
 	IoClient, BadBlocks,
 };
-use client::bad_blocks;
+use client::{bad_blocks, bad_block_reports, state_changes};
+use db::IoStats;
 use encoded;
 use engines::{EthEngine, EpochTransition, ForkChoice};
 use error::{
@@ -65,6 +69,7 @@ use miner::{Miner, MinerService};
 use ethcore_miner::pool::VerifiedTransaction;
 use parking_lot::{Mutex, RwLock};
 use rand::OsRng;
+use pod_account::{self, PodAccount};
 use receipt::{Receipt, LocalizedReceipt};
 use snapshot::{self, io as snapshot_io};
 use spec::Spec;
@@ -75,6 +80,9 @@ use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Databa
 use transaction::{self, LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, Action};
 use types::filter::Filter;
 use types::ancestry_action::AncestryAction;
+use types::basic_account::BasicAccount;
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 use verification;
 use verification::{PreverifiedBlock, Verifier, BlockQueue};
 use verification::queue::kind::blocks::Unverified;
@@ -105,6 +113,8 @@ pub struct ClientReport {
 	pub gas_processed: U256,
 	/// Memory used by state DB
 	pub state_db_mem: usize,
+	/// I/O counters of the client database
+	pub io_stats: IoStats,
 }
 
 impl ClientReport {
@@ -127,6 +137,7 @@ impl<'a> ::std::ops::Sub<&'a ClientReport> for ClientReport {
 		self.transactions_applied -= other.transactions_applied;
 		self.gas_processed = self.gas_processed - other.gas_processed;
 		self.state_db_mem = higher_mem - lower_mem;
+		self.io_stats = self.io_stats - &other.io_stats;
 
 		self
 	}
@@ -239,6 +250,10 @@ pub struct Client {
 	/// A closure to call when we want to restart the client
 	exit_handler: Mutex<Option<Box<Fn(String) + 'static + Send>>>,
 
+	/// Number, parent hash and transaction gas prices of the non-empty blocks sampled by the
+	/// last `gas_price_corpus` call, so that only new blocks have to be read on the next one.
+	gas_price_samples: Mutex<HashMap<H256, (BlockNumber, H256, Vec<U256>)>>,
+
 	importer: Importer,
 }
 
@@ -433,6 +448,12 @@ impl Importer {
 		// Final Verification
 		if let Err(e) = self.verifier.verify_block_final(&header, locked_block.block().header()) {
 			warn!(target: "client", "Stage 5 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+			if let Some(ref dir) = client.config.bad_block_reports {
+				match bad_block_reports::write(dir, &header, &parent, locked_block.drain(), &client.factories, format!("{:?}", e)) {
+					Ok(path) => warn!(target: "client", "Bad block report written to {}", path.display()),
+					Err(e) => warn!(target: "client", "Failed to write bad block report: {}", e),
+				}
+			}
 			return Err(());
 		}
 
@@ -779,6 +800,7 @@ impl Client {
 			registrar: registry::Registry::default(),
 			registrar_address,
 			exit_handler: Mutex::new(None),
+			gas_price_samples: Mutex::new(HashMap::new()),
 			importer,
 			config,
 		});
@@ -1070,6 +1092,7 @@ impl Client {
 	pub fn report(&self) -> ClientReport {
 		let mut report = self.report.read().clone();
 		report.state_db_mem = self.state_db.read().mem_used();
+		report.io_stats = self.db.read().io_stats();
 		report
 	}
 
@@ -1406,6 +1429,12 @@ impl ImportBlock for Client {
 			bail!(BlockImportErrorKind::Block(BlockError::UnknownParent(unverified.parent_hash())));
 		}
 
+		// don't let the queue grow past its limits, sync pauses until it drains.
+		let queue_info = self.importer.block_queue.queue_info();
+		if queue_info.is_full() {
+			bail!(BlockImportErrorKind::Queue(QueueErrorKind::Full(queue_info.max_queue_size)));
+		}
+
 		let raw = unverified.bytes.clone();
 		match self.importer.block_queue.import(unverified).map_err(Into::into) {
 			Ok(res) => Ok(res),
@@ -1544,6 +1573,11 @@ impl Call for Client {
 		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
 		binary_chop(lower, upper, cond)
 	}
+
+	fn override_state(&self, state: &mut Self::State, overrides: &StateOverride) -> Result<(), CallError> {
+		state.override_accounts(overrides).map_err(ExecutionError::from)?;
+		Ok(())
+	}
 }
 
 impl EngineInfo for Client {
@@ -1671,6 +1705,10 @@ impl BlockChainClient for Client {
 		Self::block_hash(&chain, id)
 	}
 
+	fn block_hashes_with_skip(&self, start: BlockNumber, max: usize, skip: u64, reverse: bool) -> Vec<H256> {
+		self.chain.read().block_hashes_with_skip(start, max, skip, reverse)
+	}
+
 	fn code(&self, address: &Address, state: StateOrBlock) -> Option<Option<Bytes>> {
 		let result = match state {
 			StateOrBlock::State(s) => s.code(address).ok(),
@@ -1700,7 +1738,7 @@ impl BlockChainClient for Client {
 		};
 
 		let (root, db) = state.drop();
-		let trie = match self.factories.trie.readonly(db.as_hashdb(), &root) {
+		let trie = match FatDB::new(db.as_hashdb(), &root) {
 			Ok(trie) => trie,
 			_ => {
 				trace!(target: "fatdb", "list_accounts: Couldn't open the DB");
@@ -1708,10 +1746,7 @@ impl BlockChainClient for Client {
 			}
 		};
 
-		let mut iter = match trie.iter() {
-			Ok(iter) => iter,
-			_ => return None,
-		};
+		let mut iter = FatItemIterator::new(&trie, Direction::Forward);
 
 		if let Some(after) = after {
 			if let Err(e) = iter.seek(after) {
@@ -1722,8 +1757,12 @@ impl BlockChainClient for Client {
 			}
 		}
 
-		let accounts = iter.filter_map(|item| {
-			item.ok().map(|(addr, _)| Address::from_slice(&addr))
+		let accounts = iter.filter_map(|item| match item {
+			Ok((addr, _)) => Some(Address::from_slice(&addr)),
+			Err(e) => {
+				trace!(target: "fatdb", "list_accounts: Skipping account: {}", e);
+				None
+			}
 		}).take(count as usize).collect();
 
 		Some(accounts)
@@ -1747,7 +1786,7 @@ impl BlockChainClient for Client {
 
 		let (_, db) = state.drop();
 		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), keccak(account));
-		let trie = match self.factories.trie.readonly(account_db.as_hashdb(), &root) {
+		let trie = match FatDB::new(account_db.as_hashdb(), &root) {
 			Ok(trie) => trie,
 			_ => {
 				trace!(target: "fatdb", "list_storage: Couldn't open the DB");
@@ -1755,10 +1794,7 @@ impl BlockChainClient for Client {
 			}
 		};
 
-		let mut iter = match trie.iter() {
-			Ok(iter) => iter,
-			_ => return None,
-		};
+		let mut iter = FatItemIterator::new(&trie, Direction::Forward);
 
 		if let Some(after) = after {
 			if let Err(e) = iter.seek(after) {
@@ -1769,13 +1805,37 @@ impl BlockChainClient for Client {
 			}
 		}
 
-		let keys = iter.filter_map(|item| {
-			item.ok().map(|(key, _)| H256::from_slice(&key))
+		let keys = iter.filter_map(|item| match item {
+			Ok((key, _)) => Some(H256::from_slice(&key)),
+			Err(e) => {
+				trace!(target: "fatdb", "list_storage: Skipping storage key: {}", e);
+				None
+			}
 		}).take(count as usize).collect();
 
 		Some(keys)
 	}
 
+	fn state_diff(&self, id: BlockId) -> Option<StateDiff> {
+		if !self.factories.trie.is_fat() {
+			trace!(target: "fatdb", "state_diff: Not a fat DB");
+			return None;
+		}
+
+		let pre_root = *self.state_at_beginning(id)?.root();
+		let state = self.state_at(id)?;
+		let post_root = *state.root();
+		let (_, db) = state.drop();
+
+		match state_diff(db.as_hashdb(), &self.factories, &pre_root, &post_root) {
+			Ok(diff) => Some(diff),
+			Err(e) => {
+				trace!(target: "fatdb", "state_diff: Couldn't compare the states: {}", e);
+				None
+			}
+		}
+	}
+
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
@@ -2020,6 +2080,42 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, ::miner::PendingOrdering::Priority)
 	}
 
+	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
+		let mut samples = self.gas_price_samples.lock();
+		let mut sampled = HashMap::new();
+		let mut corpus = Vec::new();
+		let mut h = self.chain.read().best_block_hash();
+
+		// Keep going back past empty blocks until at least one transaction was seen.
+		// Blocks are looked up by hash, so samples of blocks reorganised away are never used.
+		'blocks: while corpus.is_empty() && sample_size > 0 {
+			for _ in 0..sample_size {
+				// don't hold the chain lock across the whole walk, imports may be waiting on it.
+				let sample = samples.remove(&h).or_else(|| self.chain.read().block(&h).map(|block| {
+					let gas_prices = block.transaction_views().iter().map(|t| t.gas_price()).collect();
+					(block.number(), block.parent_hash(), gas_prices)
+				}));
+				let (number, parent_hash, gas_prices) = match sample {
+					Some(sample) => sample,
+					None => break 'blocks,
+				};
+
+				if number == 0 {
+					break 'blocks;
+				}
+				// empty blocks are cheap to read again and would only fill the cache.
+				if !gas_prices.is_empty() {
+					corpus.extend_from_slice(&gas_prices);
+					sampled.insert(h, (number, parent_hash, gas_prices));
+				}
+				h = parent_hash;
+			}
+		}
+
+		*samples = sampled;
+		corpus.into()
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -2378,6 +2474,56 @@ impl Drop for Client {
 	}
 }
 
+/// Compare the state tries with roots `pre` and `post` in a fat database, without executing anything.
+fn state_diff(db: &HashDB<KeccakHasher>, factories: &Factories, pre_root: &H256, post_root: &H256) -> ethtrie::Result<StateDiff> {
+	let mut raw = BTreeMap::new();
+
+	for change in state_changes::account_changes(db, factories, pre_root, post_root)? {
+		let address = Address::from_slice(&fat_preimage(db, &change.address_hash)?);
+		let account_db = factories.accountdb.readonly(db, change.address_hash);
+
+		// only the changed slots are read, `diff_pod` treats the others as unchanged.
+		let mut pre_storage = BTreeMap::new();
+		let mut post_storage = BTreeMap::new();
+		for slot in change.storage {
+			let key = H256::from_slice(&fat_preimage(&*account_db, &slot.key_hash)?);
+			if let Some(value) = slot.pre {
+				pre_storage.insert(key, value.into());
+			}
+			if let Some(value) = slot.post {
+				post_storage.insert(key, value.into());
+			}
+		}
+
+		let to_pod = |account: Option<BasicAccount>, storage: BTreeMap<H256, H256>| -> ethtrie::Result<Option<PodAccount>> {
+			let account = match account {
+				Some(account) => account,
+				None => return Ok(None),
+			};
+			let code = match account.code_hash == KECCAK_EMPTY {
+				true => Vec::new(),
+				false => account_db.get(&account.code_hash)
+					.ok_or_else(|| Box::new(TrieError::IncompleteDatabase(account.code_hash)))?
+					.into_vec(),
+			};
+			Ok(Some(PodAccount { balance: account.balance, nonce: account.nonce, code: Some(code), storage }))
+		};
+
+		let pre = to_pod(change.pre, pre_storage)?;
+		let post = to_pod(change.post, post_storage)?;
+		if let Some(diff) = pod_account::diff_pod(pre.as_ref(), post.as_ref()) {
+			raw.insert(address, diff);
+		}
+	}
+
+	Ok(StateDiff { raw })
+}
+
+/// Look up the key hashed into `hash` among the preimages kept by a fat trie.
+fn fat_preimage(db: &HashDB<KeccakHasher>, hash: &H256) -> ethtrie::Result<DBValue> {
+	state_changes::preimage(db, hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(keccak(hash))))
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
 fn transaction_receipt(machine: &::machine::EthereumMachine, mut tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
@@ -2559,6 +2705,68 @@ mod tests {
 			outcome: TransactionOutcome::StateRoot(state_root),
 		});
 	}
+
+	#[test]
+	fn gas_price_corpus_reuses_samples() {
+		use client::{BlockChainClient, ChainInfo};
+		use ethereum_types::U256;
+		use test_helpers::generate_dummy_client_with_data;
+
+		let client = generate_dummy_client_with_data(3, 1, slice_into![1, 2, 3]);
+		assert_eq!(*client.gas_price_corpus(3), vec![U256::from(1), U256::from(2), U256::from(3)]);
+		assert_eq!(client.gas_price_samples.lock().len(), 3);
+
+		// a cached sample is used instead of the block.
+		let best = client.chain_info().best_block_hash;
+		client.gas_price_samples.lock().get_mut(&best).unwrap().2 = vec![U256::from(7)];
+		assert_eq!(*client.gas_price_corpus(3), vec![U256::from(1), U256::from(2), U256::from(7)]);
+
+		// only the blocks visited by the last call are kept.
+		assert_eq!(*client.gas_price_corpus(1), vec![U256::from(7)]);
+		assert_eq!(client.gas_price_samples.lock().keys().collect::<Vec<_>>(), vec![&best]);
+	}
+
+	#[test]
+	fn gas_price_corpus_skips_caching_empty_blocks() {
+		use test_helpers::generate_dummy_client_with_data;
+
+		let client = generate_dummy_client_with_data(3, 0, slice_into![]);
+		assert!(client.gas_price_corpus(3).is_empty());
+		assert!(client.gas_price_samples.lock().is_empty());
+	}
+
+	#[test]
+	fn gas_price_corpus_follows_reorgs() {
+		use client::{BlockChainClient, BlockId, BlockInfo, ChainInfo, ImportBlock};
+		use ethereum_types::U256;
+		use header::Header;
+		use test_helpers::{create_test_block, generate_dummy_client_with_data};
+		use verification::queue::kind::blocks::Unverified;
+
+		let client = generate_dummy_client_with_data(3, 1, slice_into![1, 2, 3]);
+		assert_eq!(client.gas_price_corpus(3).len(), 3);
+
+		// a heavier fork of empty blocks replaces all the blocks with transactions.
+		let genesis = client.block_header(BlockId::Number(0)).unwrap().decode().unwrap();
+		let mut parent = genesis.clone();
+		for number in 1..5 {
+			let mut header = Header::new();
+			header.set_parent_hash(parent.hash());
+			header.set_number(number);
+			header.set_timestamp(parent.timestamp() + 1);
+			header.set_gas_limit(*genesis.gas_limit());
+			header.set_difficulty(*genesis.difficulty() * U256::from(1000));
+			header.set_state_root(*genesis.state_root());
+			client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()).unwrap();
+			parent = header;
+		}
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_hash, parent.hash());
+
+		assert!(client.gas_price_corpus(3).is_empty());
+		assert!(client.gas_price_samples.lock().is_empty());
+	}
 }
 
 /// Queue some items to be processed by IO client.
@@ -16,6 +16,7 @@
 
 use std::str::FromStr;
 use std::fmt::{Display, Formatter, Error as FmtError};
+use std::path::PathBuf;
 
 use verification::{VerifierType, QueueConfig};
 use journaldb;
@@ -120,6 +121,8 @@ pub struct ClientConfig {
 	pub check_seal: bool,
 	/// Maximal number of transactions queued for verification in a separate thread.
 	pub transaction_verification_queue_size: usize,
+	/// Directory to write a report into for every block whose execution doesn't match its header.
+	pub bad_block_reports: Option<PathBuf>,
 }
 
 impl Default for ClientConfig {
@@ -144,6 +147,7 @@ impl Default for ClientConfig {
 			history_mem: 32 * mb,
 			check_seal: true,
 			transaction_verification_queue_size: 8192,
+			bad_block_reports: None,
 		}
 	}
 }
@@ -17,12 +17,14 @@
 //! Blockchain database client.
 
 mod ancient_import;
+mod bad_block_reports;
 mod bad_blocks;
 mod client;
 mod config;
 #[cfg(any(test, feature = "test-helpers"))]
 mod evm_test_client;
 mod io_message;
+mod state_changes;
 #[cfg(any(test, feature = "test-helpers"))]
 mod test_client;
 mod trace;
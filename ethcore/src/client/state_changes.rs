@@ -0,0 +1,136 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Comparison of two states kept in the same database, without executing anything.
+
+use ethereum_types::{H256, U256};
+use ethtrie::{self, Change};
+use factory::Factories;
+use hash::{keccak, KECCAK_NULL_RLP};
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
+use kvdb::DBValue;
+use rlp::Decodable;
+use trie::TrieError;
+use types::basic_account::BasicAccount;
+
+/// An account which differs between two states.
+pub struct AccountChange {
+	/// Hash of the account address.
+	pub address_hash: H256,
+	/// The account before, if it existed.
+	pub pre: Option<BasicAccount>,
+	/// The account after, if it exists.
+	pub post: Option<BasicAccount>,
+	/// The storage slots which differ.
+	pub storage: Vec<StorageChange>,
+}
+
+/// A storage slot which differs between two states.
+pub struct StorageChange {
+	/// Hash of the slot key.
+	pub key_hash: H256,
+	/// The value before, if it was set.
+	pub pre: Option<U256>,
+	/// The value after, if it is set.
+	pub post: Option<U256>,
+}
+
+/// List the accounts and storage slots which differ between the state tries with roots
+/// `pre_root` and `post_root`. Only the changed parts of the tries are read.
+pub fn account_changes(db: &HashDB<KeccakHasher>, factories: &Factories, pre_root: &H256, post_root: &H256) -> ethtrie::Result<Vec<AccountChange>> {
+	let mut accounts = Vec::new();
+
+	for (key, change) in ethtrie::diff(db, pre_root, post_root)? {
+		let address_hash = H256::from_slice(&key);
+		let (pre, post) = match change {
+			Change::Added(post) => (None, Some(decode::<BasicAccount>(&address_hash, &post)?)),
+			Change::Removed(pre) => (Some(decode::<BasicAccount>(&address_hash, &pre)?), None),
+			Change::Changed(pre, post) => (Some(decode::<BasicAccount>(&address_hash, &pre)?), Some(decode::<BasicAccount>(&address_hash, &post)?)),
+		};
+
+		let storage_root = |account: &Option<BasicAccount>| account.as_ref().map_or(KECCAK_NULL_RLP, |a| a.storage_root);
+		let account_db = factories.accountdb.readonly(db, address_hash);
+		let mut storage = Vec::new();
+		for (key, change) in ethtrie::diff(&*account_db, &storage_root(&pre), &storage_root(&post))? {
+			let key_hash = H256::from_slice(&key);
+			let value = |value: DBValue| decode::<U256>(&key_hash, &value);
+			let (pre, post) = match change {
+				Change::Added(post) => (None, Some(value(post)?)),
+				Change::Removed(pre) => (Some(value(pre)?), None),
+				Change::Changed(pre, post) => (Some(value(pre)?), Some(value(post)?)),
+			};
+			storage.push(StorageChange { key_hash, pre, post });
+		}
+
+		accounts.push(AccountChange { address_hash, pre, post, storage });
+	}
+
+	Ok(accounts)
+}
+
+/// The key hashed into `hash`, if the trie is a fat one.
+pub fn preimage(db: &HashDB<KeccakHasher>, hash: &H256) -> Option<DBValue> {
+	db.get(&keccak(hash))
+}
+
+fn decode<T: Decodable>(key_hash: &H256, rlp: &[u8]) -> ethtrie::Result<T> {
+	::rlp::decode(rlp).map_err(|e| Box::new(TrieError::DecoderError(*key_hash, e)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethereum_types::Address;
+	use state::CleanupMode;
+	use test_helpers::get_temp_state;
+
+	#[test]
+	fn lists_changed_accounts_and_slots() {
+		let mut state = get_temp_state();
+		let a = Address::from(1);
+		let b = Address::from(2);
+		state.add_balance(&a, &100.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, H256::from(1), H256::from(3)).unwrap();
+		state.add_balance(&b, &5.into(), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+		let pre_root = *state.root();
+
+		state.add_balance(&a, &23.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, H256::from(1), H256::zero()).unwrap();
+		state.set_storage(&a, H256::from(2), H256::from(7)).unwrap();
+		state.commit().unwrap();
+		let (post_root, db) = state.drop();
+
+		let changes = account_changes(db.as_hashdb(), &Factories::default(), &pre_root, &post_root).unwrap();
+		assert_eq!(changes.len(), 1);
+		let change = &changes[0];
+		assert_eq!(change.address_hash, keccak(&a));
+		assert_eq!(change.pre.as_ref().map(|a| a.balance), Some(100.into()));
+		assert_eq!(change.post.as_ref().map(|a| a.balance), Some(123.into()));
+
+		let mut storage: Vec<_> = change.storage.iter().map(|s| (s.key_hash, s.pre, s.post)).collect();
+		storage.sort();
+		let mut expected = vec![
+			(keccak(&H256::from(1)), Some(3.into()), None),
+			(keccak(&H256::from(2)), None, Some(7.into())),
+		];
+		expected.sort();
+		assert_eq!(storage, expected);
+
+		assert!(account_changes(db.as_hashdb(), &Factories::default(), &post_root, &post_root).unwrap().is_empty());
+	}
+}
@@ -37,7 +37,7 @@ use blockchain::{TreeRoute, BlockReceipts};
 use client::{
 	Nonce, Balance, ChainInfo, BlockInfo, ReopenBlock, CallContract, TransactionInfo, RegistryInfo,
 	PrepareOpenBlock, BlockChainClient, BlockChainInfo, BlockStatus, BlockId, Mode,
-	TransactionId, UncleId, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError,
+	TransactionId, UncleId, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError, BlockImportErrorKind,
 	ProvingBlockChainClient, ScheduleInfo, ImportSealedBlock, BroadcastProposalBlock, ImportBlock, StateOrBlock,
 	Call, StateClient, EngineInfo, AccountData, BlockChain, BlockProducer, SealedBlockImporter, IoClient,
 	BadBlocks,
@@ -47,12 +47,14 @@ use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt, TransactionOutcome};
-use error::{Error, ImportResult};
+use error::{Error, ImportResult, QueueErrorKind};
 use vm::Schedule;
 use miner::{self, Miner, MinerService};
 use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 use verification::queue::QueueInfo;
 use verification::queue::kind::blocks::Unverified;
 use block::{OpenBlock, SealedBlock, ClosedBlock};
@@ -99,6 +101,8 @@ pub struct TestBlockChainClient {
 	pub error_on_logs: RwLock<Option<BlockId>>,
 	/// Block queue size.
 	pub queue_size: AtomicUsize,
+	/// Block queue size limit.
+	pub queue_limit: AtomicUsize,
 	/// Miner
 	pub miner: Arc<Miner>,
 	/// Spec
@@ -173,6 +177,7 @@ impl TestBlockChainClient {
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
+			queue_limit: AtomicUsize::new(0),
 			miner: Arc::new(Miner::new_for_tests(&spec, None)),
 			spec: spec,
 			latest_block_timestamp: RwLock::new(10_000_000),
@@ -227,6 +232,11 @@ impl TestBlockChainClient {
 		self.queue_size.store(size, AtomicOrder::Relaxed);
 	}
 
+	/// Set block queue size limit for testing
+	pub fn set_queue_limit(&self, limit: usize) {
+		self.queue_limit.store(limit, AtomicOrder::Relaxed);
+	}
+
 	/// Set timestamp assigned to latest sealed block
 	pub fn set_latest_block_timestamp(&self, ts: u64) {
 		*self.latest_block_timestamp.write() = ts;
@@ -524,6 +534,11 @@ impl RegistryInfo for TestBlockChainClient {
 
 impl ImportBlock for TestBlockChainClient {
 	fn import_block(&self, unverified: Unverified) -> Result<H256, BlockImportError> {
+		let queue_info = self.queue_info();
+		if queue_info.is_full() {
+			return Err(BlockImportErrorKind::Queue(QueueErrorKind::Full(queue_info.max_queue_size)).into());
+		}
+
 		let header = unverified.header;
 		let h = header.hash();
 		let number: usize = header.number() as usize;
@@ -588,6 +603,10 @@ impl Call for TestBlockChainClient {
 	fn estimate_gas(&self, _t: &SignedTransaction, _state: &Self::State, _header: &Header) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
+
+	fn override_state(&self, _state: &mut Self::State, _overrides: &StateOverride) -> Result<(), CallError> {
+		Ok(())
+	}
 }
 
 impl StateInfo for () {
@@ -671,6 +690,11 @@ impl BlockChainClient for TestBlockChainClient {
 	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
 		None
 	}
+
+	fn state_diff(&self, _id: BlockId) -> Option<StateDiff> {
+		None
+	}
+
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
 	}
@@ -805,7 +829,7 @@ impl BlockChainClient for TestBlockChainClient {
 			verified_queue_size: self.queue_size.load(AtomicOrder::Relaxed),
 			unverified_queue_size: 0,
 			verifying_queue_size: 0,
-			max_queue_size: 0,
+			max_queue_size: self.queue_limit.load(AtomicOrder::Relaxed),
 			max_mem_use: 0,
 			mem_used: 0,
 		}
@@ -51,6 +51,8 @@ use types::call_analytics::CallAnalytics;
 use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::pruning_info::PruningInfo;
+use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 
 /// State information to be used during client query
 pub enum StateOrBlock {
@@ -191,6 +193,9 @@ pub trait Call {
 
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, state: &Self::State, header: &Header) -> Result<U256, CallError>;
+
+	/// Changes the accounts of a state which is only used for calls.
+	fn override_state(&self, state: &mut Self::State, overrides: &StateOverride) -> Result<(), CallError>;
 }
 
 /// Provides `engine` method
@@ -240,6 +245,27 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Get block hash.
 	fn block_hash(&self, id: BlockId) -> Option<H256>;
 
+	/// Get the hashes of up to `max` canonical blocks, starting at `start` and stepping over
+	/// `skip` blocks between each, towards genesis if `reverse` is set.
+	/// The result is truncated at genesis, or at the first block whose hash isn't known.
+	fn block_hashes_with_skip(&self, start: BlockNumber, max: usize, skip: u64, reverse: bool) -> Vec<H256> {
+		let step = skip.saturating_add(1);
+		let mut hashes = Vec::with_capacity(max);
+		let mut number = start;
+		while hashes.len() < max {
+			match self.block_hash(BlockId::Number(number)) {
+				Some(hash) => hashes.push(hash),
+				None => break,
+			}
+			number = match reverse {
+				true if number < step => break,
+				true => number - step,
+				false => number.saturating_add(step),
+			};
+		}
+		hashes
+	}
+
 	/// Get address code at given block's state.
 	fn code(&self, address: &Address, state: StateOrBlock) -> Option<Option<Bytes>>;
 
@@ -272,6 +298,10 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
 
+	/// Get the changes the block `id` made to the state by comparing its state trie with its parent's,
+	/// if fat DB is in operation and both states are available, otherwise `None`.
+	fn state_diff(&self, id: BlockId) -> Option<StateDiff>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -16,13 +16,17 @@
 
 //! Database utilities and definitions.
 
+use std::{io, ops};
 use std::ops::Deref;
 use std::hash::Hash;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
 use parking_lot::RwLock;
-use kvdb::{DBTransaction, KeyValueDB};
+use kvdb::{DBOp, DBTransaction, DBValue, KeyValueDB};
 
 use rlp;
+use rlp_compress::{compress_nested, decompress_nested, is_canonical, snapshot_swapper};
 
 // database columns
 /// Column for State
@@ -237,3 +241,523 @@ impl<KVDB: KeyValueDB + ?Sized> Readable for KVDB {
 		}
 	}
 }
+
+/// Upper bounds, in milliseconds, of the commit latency buckets kept in `IoStats`.
+/// Commits slower than the last bound land in an extra overflow bucket.
+pub const COMMIT_LATENCY_BUCKETS: [u64; 4] = [1, 10, 100, 1000];
+
+/// Snapshot of the I/O counters kept by a `StatsDB`.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+pub struct IoStats {
+	/// Number of point reads.
+	pub reads: usize,
+	/// Number of point reads which found a value.
+	pub reads_found: usize,
+	/// Number of inserts and deletes handed to the database.
+	pub writes: usize,
+	/// Total size of the values read.
+	pub bytes_read: usize,
+	/// Total size of the keys and values written.
+	pub bytes_written: usize,
+	/// Number of transactions handed to the database.
+	pub transactions: usize,
+	/// Number of commits (`write` and `flush` calls) per latency bucket.
+	pub commit_latency: [usize; 5],
+}
+
+impl<'a> ops::Sub<&'a IoStats> for IoStats {
+	type Output = Self;
+
+	// saturating, since the counters restart when the database is reset or replaced
+	fn sub(mut self, other: &'a IoStats) -> Self {
+		self.reads = self.reads.saturating_sub(other.reads);
+		self.reads_found = self.reads_found.saturating_sub(other.reads_found);
+		self.writes = self.writes.saturating_sub(other.writes);
+		self.bytes_read = self.bytes_read.saturating_sub(other.bytes_read);
+		self.bytes_written = self.bytes_written.saturating_sub(other.bytes_written);
+		self.transactions = self.transactions.saturating_sub(other.transactions);
+		for (bucket, other) in self.commit_latency.iter_mut().zip(other.commit_latency.iter()) {
+			*bucket = bucket.saturating_sub(*other);
+		}
+
+		self
+	}
+}
+
+#[derive(Default)]
+struct IoCounters {
+	reads: AtomicUsize,
+	reads_found: AtomicUsize,
+	writes: AtomicUsize,
+	bytes_read: AtomicUsize,
+	bytes_written: AtomicUsize,
+	transactions: AtomicUsize,
+	commit_latency: [AtomicUsize; 5],
+}
+
+/// Key-value database wrapper counting the I/O going through it.
+///
+/// Counters are relaxed atomics, so a snapshot taken while other threads use the database
+/// may be slightly inconsistent between fields.
+pub struct StatsDB<DB> {
+	inner: DB,
+	counters: IoCounters,
+}
+
+impl<DB: KeyValueDB> StatsDB<DB> {
+	/// Wrap the given database.
+	pub fn new(inner: DB) -> Self {
+		StatsDB {
+			inner,
+			counters: IoCounters::default(),
+		}
+	}
+
+	/// Snapshot of the counters accumulated since creation or the last `reset_stats`.
+	pub fn stats(&self) -> IoStats {
+		let c = &self.counters;
+		let load = |counter: &AtomicUsize| counter.load(AtomicOrdering::Relaxed);
+		let mut commit_latency = [0; 5];
+		for (bucket, counter) in commit_latency.iter_mut().zip(c.commit_latency.iter()) {
+			*bucket = load(counter);
+		}
+
+		IoStats {
+			reads: load(&c.reads),
+			reads_found: load(&c.reads_found),
+			writes: load(&c.writes),
+			bytes_read: load(&c.bytes_read),
+			bytes_written: load(&c.bytes_written),
+			transactions: load(&c.transactions),
+			commit_latency,
+		}
+	}
+
+	/// Set all counters back to zero.
+	pub fn reset_stats(&self) {
+		let c = &self.counters;
+		for counter in &[&c.reads, &c.reads_found, &c.writes, &c.bytes_read, &c.bytes_written, &c.transactions] {
+			counter.store(0, AtomicOrdering::Relaxed);
+		}
+		for counter in &c.commit_latency {
+			counter.store(0, AtomicOrdering::Relaxed);
+		}
+	}
+
+	fn count(counter: &AtomicUsize, n: usize) {
+		counter.fetch_add(n, AtomicOrdering::Relaxed);
+	}
+
+	fn count_transaction(&self, transaction: &DBTransaction) {
+		let bytes = transaction.ops.iter().map(|op| match *op {
+			DBOp::Insert { ref key, ref value, .. } => key.len() + value.len(),
+			DBOp::Delete { ref key, .. } => key.len(),
+		}).sum();
+
+		Self::count(&self.counters.transactions, 1);
+		Self::count(&self.counters.writes, transaction.ops.len());
+		Self::count(&self.counters.bytes_written, bytes);
+	}
+
+	fn count_commit(&self, elapsed: Duration) {
+		let millis = elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000;
+		let bucket = COMMIT_LATENCY_BUCKETS.iter()
+			.position(|bound| millis < *bound)
+			.unwrap_or(COMMIT_LATENCY_BUCKETS.len());
+		Self::count(&self.counters.commit_latency[bucket], 1);
+	}
+}
+
+impl<DB: KeyValueDB> KeyValueDB for StatsDB<DB> {
+	fn get(&self, col: Option<u32>, key: &[u8]) -> io::Result<Option<DBValue>> {
+		let value = self.inner.get(col, key)?;
+		Self::count(&self.counters.reads, 1);
+		if let Some(ref value) = value {
+			Self::count(&self.counters.reads_found, 1);
+			Self::count(&self.counters.bytes_read, value.len());
+		}
+		Ok(value)
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		let value = self.inner.get_by_prefix(col, prefix);
+		Self::count(&self.counters.reads, 1);
+		if let Some(ref value) = value {
+			Self::count(&self.counters.reads_found, 1);
+			Self::count(&self.counters.bytes_read, value.len());
+		}
+		value
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		self.count_transaction(&transaction);
+		self.inner.write_buffered(transaction)
+	}
+
+	fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+		self.count_transaction(&transaction);
+		let start = Instant::now();
+		let result = self.inner.write(transaction);
+		self.count_commit(start.elapsed());
+		result
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		let start = Instant::now();
+		let result = self.inner.flush();
+		self.count_commit(start.elapsed());
+		result
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.inner.iter(col)
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &'a [u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		self.inner.iter_from_prefix(col, prefix)
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		self.inner.restore(new_db)
+	}
+}
+
+/// Key in `COL_NODE_INFO` recording that `COL_STATE` values are compressed.
+const STATE_COMPRESSION_KEY: &'static [u8] = b"state_compression";
+
+/// Version of the compressed format recorded under `STATE_COMPRESSION_KEY`.
+const STATE_COMPRESSION_VERSION: u8 = 1;
+
+/// Tag byte of a `COL_STATE` value stored as is, used only if the value itself starts with a tag.
+const RAW_VALUE: u8 = 0;
+
+/// Tag byte of a `COL_STATE` value packed with `compress_nested`.
+const PACKED_VALUE: u8 = 1;
+
+/// Key-value database wrapper compressing the values stored in `COL_STATE`.
+///
+/// Trie nodes are compressed with `rlp_compress::compress_nested`, which replaces the empty
+/// storage root and code hash inside account leaves. The column also holds code, preimages
+/// and journal records, so only canonical rlp is packed, and only if that makes it shorter.
+/// Packed values are prefixed with a tag byte. Other values are stored as is, unless they
+/// start with a tag byte themselves, which rlp items never do.
+///
+/// Whether a database holds compressed state is recorded in it: compression is used if the
+/// flag is present, or if the database doesn't contain any state yet, in which case the flag
+/// is written. Databases with raw state keep storing raw values.
+pub struct CompressedStateDB<DB> {
+	inner: DB,
+	enabled: AtomicBool,
+}
+
+impl<DB: KeyValueDB> CompressedStateDB<DB> {
+	/// Wrap the given database, enabling compression if it records it or holds no state.
+	pub fn open(inner: DB) -> io::Result<Self> {
+		let enabled = Self::detect(&inner)?;
+		Ok(CompressedStateDB {
+			inner,
+			enabled: AtomicBool::new(enabled),
+		})
+	}
+
+	/// Whether `COL_STATE` values are compressed.
+	pub fn is_compressed(&self) -> bool {
+		self.enabled.load(AtomicOrdering::SeqCst)
+	}
+
+	fn detect(db: &DB) -> io::Result<bool> {
+		if db.get(COL_NODE_INFO, STATE_COMPRESSION_KEY)?.is_some() {
+			return Ok(true);
+		}
+		if db.iter(COL_STATE).next().is_some() {
+			return Ok(false);
+		}
+
+		let mut batch = DBTransaction::new();
+		batch.put(COL_NODE_INFO, STATE_COMPRESSION_KEY, &[STATE_COMPRESSION_VERSION]);
+		db.write(batch)?;
+		Ok(true)
+	}
+
+	fn applies_to(&self, col: Option<u32>) -> bool {
+		col == COL_STATE && self.is_compressed()
+	}
+
+	fn compress(&self, transaction: DBTransaction) -> DBTransaction {
+		if !self.is_compressed() {
+			return transaction;
+		}
+
+		let mut compressed = DBTransaction::with_capacity(transaction.ops.len());
+		for op in transaction.ops {
+			match op {
+				DBOp::Insert { col, ref key, ref value } if col == COL_STATE => match pack(value) {
+					Some(packed) => compressed.put_vec(col, key, packed),
+					None => compressed.put(col, key, value),
+				},
+				op => compressed.ops.push(op),
+			}
+		}
+		compressed
+	}
+}
+
+/// Encoding of a `COL_STATE` value, or `None` if it is stored as is.
+fn pack(value: &[u8]) -> Option<Vec<u8>> {
+	if is_canonical(value) {
+		let packed = compress_nested(value, snapshot_swapper());
+		if 1 + packed.len() < value.len() {
+			return Some(tagged(PACKED_VALUE, &packed));
+		}
+	}
+	match value.first() {
+		Some(&RAW_VALUE) | Some(&PACKED_VALUE) => Some(tagged(RAW_VALUE, value)),
+		_ => None,
+	}
+}
+
+/// Reverse of `pack`, or `None` if the value was stored as is.
+fn unpack(value: &[u8]) -> Option<Vec<u8>> {
+	match value.split_first() {
+		Some((&PACKED_VALUE, packed)) => Some(decompress_nested(packed, snapshot_swapper()).into_vec()),
+		Some((&RAW_VALUE, raw)) => Some(raw.to_vec()),
+		_ => None,
+	}
+}
+
+fn tagged(tag: u8, value: &[u8]) -> Vec<u8> {
+	let mut tagged = Vec::with_capacity(1 + value.len());
+	tagged.push(tag);
+	tagged.extend_from_slice(value);
+	tagged
+}
+
+impl<DB: KeyValueDB> KeyValueDB for CompressedStateDB<DB> {
+	fn get(&self, col: Option<u32>, key: &[u8]) -> io::Result<Option<DBValue>> {
+		let value = self.inner.get(col, key)?;
+		match self.applies_to(col) {
+			true => Ok(value.map(|value| unpack(&value).map_or(value, DBValue::from_vec))),
+			false => Ok(value),
+		}
+	}
+
+	fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		let value = self.inner.get_by_prefix(col, prefix);
+		match self.applies_to(col) {
+			true => value.map(|value| unpack(&value).map_or(value, Vec::into_boxed_slice)),
+			false => value,
+		}
+	}
+
+	fn write_buffered(&self, transaction: DBTransaction) {
+		self.inner.write_buffered(self.compress(transaction))
+	}
+
+	fn write(&self, transaction: DBTransaction) -> io::Result<()> {
+		self.inner.write(self.compress(transaction))
+	}
+
+	fn flush(&self) -> io::Result<()> {
+		self.inner.flush()
+	}
+
+	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		match self.applies_to(col) {
+			true => Box::new(self.inner.iter(col).map(|(key, value)| {
+				let value = unpack(&value).map_or(value, Vec::into_boxed_slice);
+				(key, value)
+			})),
+			false => self.inner.iter(col),
+		}
+	}
+
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &'a [u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		match self.applies_to(col) {
+			true => Box::new(self.inner.iter_from_prefix(col, prefix).map(|(key, value)| {
+				let value = unpack(&value).map_or(value, Vec::into_boxed_slice);
+				(key, value)
+			})),
+			false => self.inner.iter_from_prefix(col, prefix),
+		}
+	}
+
+	fn restore(&self, new_db: &str) -> io::Result<()> {
+		self.inner.restore(new_db)?;
+		// the restored database records its own choice
+		self.enabled.store(Self::detect(&self.inner)?, AtomicOrdering::SeqCst);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use kvdb::KeyValueDB;
+	use kvdb_memorydb;
+	use super::{StatsDB, IoStats, CompressedStateDB, COL_STATE, NUM_COLUMNS};
+
+	#[test]
+	fn stats_count_reads_and_writes() {
+		let db = StatsDB::new(kvdb_memorydb::create(1));
+		assert_eq!(db.stats(), IoStats::default());
+
+		let mut batch = db.transaction();
+		batch.put(Some(0), b"key", b"value");
+		batch.delete(Some(0), b"gone");
+		db.write(batch).unwrap();
+
+		assert_eq!(db.get(Some(0), b"key").unwrap().unwrap().to_vec(), b"value".to_vec());
+		assert!(db.get(Some(0), b"gone").unwrap().is_none());
+
+		let stats = db.stats();
+		assert_eq!(stats.transactions, 1);
+		assert_eq!(stats.writes, 2);
+		assert_eq!(stats.bytes_written, 3 + 5 + 4);
+		assert_eq!(stats.reads, 2);
+		assert_eq!(stats.reads_found, 1);
+		assert_eq!(stats.bytes_read, 5);
+		assert_eq!(stats.commit_latency.iter().sum::<usize>(), 1);
+
+		db.flush().unwrap();
+		let delta = db.stats() - &stats;
+		assert_eq!(delta.commit_latency.iter().sum::<usize>(), 1);
+		assert_eq!(delta.reads, 0);
+
+		db.reset_stats();
+		assert_eq!(db.stats(), IoStats::default());
+	}
+
+	#[test]
+	fn state_compression_is_recorded() {
+		let leaf = ::rlp::encode_list::<Vec<u8>, _>(&[vec![0x20], ::rlp::NULL_RLP.to_vec()]).into_vec();
+		let account = {
+			let mut stream = ::rlp::RlpStream::new_list(4);
+			stream.append(&0u8).append(&0u8).append(&::hash::KECCAK_NULL_RLP).append(&::hash::KECCAK_EMPTY);
+			stream.out()
+		};
+		let account_leaf = ::rlp::encode_list::<Vec<u8>, _>(&[vec![0x20], account]).into_vec();
+
+		let fresh = CompressedStateDB::open(kvdb_memorydb::create(NUM_COLUMNS.unwrap())).unwrap();
+		assert!(fresh.is_compressed());
+
+		let mut batch = fresh.transaction();
+		batch.put(COL_STATE, b"leaf", &leaf);
+		batch.put(COL_STATE, b"account", &account_leaf);
+		batch.put(COL_STATE, b"raw", &[0x81]);
+		fresh.write(batch).unwrap();
+
+		assert_eq!(&*fresh.get(COL_STATE, b"leaf").unwrap().unwrap(), &leaf[..]);
+		assert_eq!(&*fresh.get(COL_STATE, b"account").unwrap().unwrap(), &account_leaf[..]);
+		assert_eq!(&*fresh.get(COL_STATE, b"raw").unwrap().unwrap(), &[0x81]);
+		assert_eq!(fresh.iter(COL_STATE).count(), 3);
+		assert!(fresh.iter(COL_STATE).all(|(key, value)| &*fresh.get(COL_STATE, &key).unwrap().unwrap() == &*value));
+		assert!(fresh.inner.get(COL_STATE, b"account").unwrap().unwrap().len() < account_leaf.len() - 60);
+		assert_eq!(&*fresh.inner.get(COL_STATE, b"leaf").unwrap().unwrap(), &leaf[..]);
+		assert_eq!(&*fresh.inner.get(COL_STATE, b"raw").unwrap().unwrap(), &[0x81]);
+
+		let reopened = CompressedStateDB::open(fresh.inner).unwrap();
+		assert!(reopened.is_compressed());
+		assert_eq!(&*reopened.get(COL_STATE, b"account").unwrap().unwrap(), &account_leaf[..]);
+
+		let legacy = kvdb_memorydb::create(NUM_COLUMNS.unwrap());
+		let mut batch = legacy.transaction();
+		batch.put(COL_STATE, b"account", &account_leaf);
+		legacy.write(batch).unwrap();
+
+		let legacy = CompressedStateDB::open(legacy).unwrap();
+		assert!(!legacy.is_compressed());
+		let mut batch = legacy.transaction();
+		batch.put(COL_STATE, b"leaf", &leaf);
+		legacy.write(batch).unwrap();
+		assert_eq!(&*legacy.inner.get(COL_STATE, b"leaf").unwrap().unwrap(), &leaf[..]);
+		assert_eq!(&*legacy.get(COL_STATE, b"account").unwrap().unwrap(), &account_leaf[..]);
+	}
+
+	#[test]
+	fn arbitrary_state_values_round_trip() {
+		let db = CompressedStateDB::open(kvdb_memorydb::create(NUM_COLUMNS.unwrap())).unwrap();
+		let values: Vec<Vec<u8>> = vec![
+			// code `DUP2 STOP`, which reads as the rlp of an empty byte string.
+			vec![0x81, 0x00],
+			vec![0x81, 0x01],
+			// list with a non-canonical long length prefix.
+			vec![0xf8, 0x02, 0x01, 0x02],
+			::rlp::NULL_RLP.to_vec(),
+			::hash::KECCAK_NULL_RLP.to_vec(),
+			// values starting with a tag byte.
+			vec![super::RAW_VALUE, 0x60],
+			vec![super::PACKED_VALUE],
+			vec![],
+		];
+
+		let mut batch = db.transaction();
+		for (i, value) in values.iter().enumerate() {
+			batch.put(COL_STATE, &[i as u8], value);
+		}
+		db.write(batch).unwrap();
+
+		for (i, value) in values.iter().enumerate() {
+			assert_eq!(&*db.get(COL_STATE, &[i as u8]).unwrap().unwrap(), &value[..]);
+		}
+		let read: Vec<_> = db.iter(COL_STATE).map(|(_, value)| value.to_vec()).collect();
+		assert_eq!(read, values);
+	}
+
+	#[test]
+	fn compression_shrinks_mixed_state() {
+		use ethereum_types::{H256, U256};
+		use keccak_hasher::KeccakHasher;
+		use memorydb::MemoryDB;
+		use trie::TrieMut;
+		use ethtrie::SecTrieDBMut;
+		use types::basic_account::BasicAccount;
+
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut storage_roots = Vec::new();
+		for i in 0..10u64 {
+			let mut storage_root = H256::default();
+			{
+				let mut storage = SecTrieDBMut::new(&mut memdb, &mut storage_root);
+				for slot in 0..20u64 {
+					storage.insert(&H256::from(slot), &::rlp::encode(&U256::from(i * 1000 + slot + 1))).unwrap();
+				}
+			}
+			storage_roots.push(storage_root);
+		}
+
+		let mut state_root = H256::default();
+		{
+			let mut state = SecTrieDBMut::new(&mut memdb, &mut state_root);
+			for i in 0..100u64 {
+				let account = BasicAccount {
+					nonce: (i % 3).into(),
+					balance: U256::from(i) * U256::from(1_000_000_000u64),
+					storage_root: storage_roots.get(i as usize).cloned().unwrap_or(::hash::KECCAK_NULL_RLP),
+					code_hash: match i < 10 {
+						true => H256::from(i + 1),
+						false => ::hash::KECCAK_EMPTY,
+					},
+				};
+				state.insert(&H256::from(i), &::rlp::encode(&account)).unwrap();
+			}
+		}
+
+		let db = CompressedStateDB::open(kvdb_memorydb::create(NUM_COLUMNS.unwrap())).unwrap();
+		let nodes: Vec<_> = memdb.drain().into_iter().map(|(key, (value, _))| (key, value)).collect();
+		let mut batch = db.transaction();
+		for &(ref key, ref value) in &nodes {
+			batch.put(COL_STATE, key, value);
+		}
+		db.write(batch).unwrap();
+
+		let (mut raw_size, mut stored_size) = (0, 0);
+		for &(ref key, ref value) in &nodes {
+			let stored = db.inner.get(COL_STATE, key).unwrap().unwrap();
+			assert!(stored.len() <= value.len());
+			assert_eq!(&*db.get(COL_STATE, key).unwrap().unwrap(), &value[..]);
+			raw_size += value.len();
+			stored_size += stored.len();
+		}
+		// the 90 plain accounts lose their empty storage root and code hash.
+		assert!(raw_size - stored_size >= 90 * 60, "{} bytes stored as {}", raw_size, stored_size);
+	}
+}
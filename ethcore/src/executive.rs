@@ -18,6 +18,7 @@
 use std::cmp;
 use std::sync::Arc;
 use hash::keccak;
+use keccak_hasher::{KeccakStream, keccak_pair};
 use ethereum_types::{H256, U256, U512, Address};
 use bytes::{Bytes, BytesRef};
 use state::{Backend as StateBackend, State, Substate, CleanupMode};
@@ -63,19 +64,16 @@ pub fn contract_address(address_scheme: CreateContractAddress, sender: &Address,
 		},
 		CreateContractAddress::FromSenderSaltAndCodeHash(salt) => {
 			let code_hash = keccak(code);
-			let mut buffer = [0u8; 1 + 20 + 32 + 32];
-			buffer[0] = 0xff;
-			&mut buffer[1..(1+20)].copy_from_slice(&sender[..]);
-			&mut buffer[(1+20)..(1+20+32)].copy_from_slice(&salt[..]);
-			&mut buffer[(1+20+32)..].copy_from_slice(&code_hash[..]);
-			(From::from(keccak(&buffer[..])), Some(code_hash))
+			let mut stream = KeccakStream::new();
+			stream.update(&[0xff]);
+			stream.update(&sender[..]);
+			stream.update(&salt[..]);
+			stream.update(&code_hash[..]);
+			(From::from(stream.finalize()), Some(code_hash))
 		},
 		CreateContractAddress::FromSenderAndCodeHash => {
 			let code_hash = keccak(code);
-			let mut buffer = [0u8; 20 + 32];
-			&mut buffer[..20].copy_from_slice(&sender[..]);
-			&mut buffer[20..].copy_from_slice(&code_hash[..]);
-			(From::from(keccak(&buffer[..])), Some(code_hash))
+			(From::from(keccak_pair(&sender[..], &code_hash[..])), Some(code_hash))
 		},
 	}
 }
@@ -135,6 +135,8 @@ extern crate macros;
 extern crate rlp_derive;
 #[macro_use]
 extern crate trace_time;
+#[macro_use]
+extern crate serde_json;
 
 #[cfg_attr(test, macro_use)]
 extern crate evm;
@@ -138,6 +138,8 @@ pub struct MinerOptions {
 
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Minimal gas price increase, in per mille, for a transaction to replace one with the same sender and nonce.
+	pub tx_queue_replace_bump: u32,
 	/// Simple senders penalization.
 	pub tx_queue_penalization: Penalization,
 	/// Do we want to mark transactions recieved locally (e.g. RPC) as local if we don't have the sending account?
@@ -164,6 +166,7 @@ impl Default for MinerOptions {
 			enable_resubmission: true,
 			infinite_pending_block: false,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_replace_bump: pool::scoring::DEFAULT_REPLACE_BUMP,
 			tx_queue_penalization: Penalization::Disabled,
 			tx_queue_no_unfamiliar_locals: false,
 			refuse_service_transactions: false,
@@ -249,7 +252,10 @@ impl Miner {
 	) -> Self {
 		let limits = options.pool_limits.clone();
 		let verifier_options = options.pool_verification_options.clone();
-		let tx_queue_strategy = options.tx_queue_strategy;
+		let scoring = pool::scoring::NonceAndGasPrice {
+			strategy: options.tx_queue_strategy,
+			replace_bump: options.tx_queue_replace_bump,
+		};
 		let nonce_cache_size = cmp::max(4096, limits.max_count / 4);
 
 		Miner {
@@ -267,7 +273,7 @@ impl Miner {
 			gas_pricer: Mutex::new(gas_pricer),
 			nonce_cache: NonceCache::new(nonce_cache_size),
 			options,
-			transaction_queue: Arc::new(TransactionQueue::new(limits, verifier_options, tx_queue_strategy)),
+			transaction_queue: Arc::new(TransactionQueue::with_scoring(limits, verifier_options, scoring)),
 			accounts,
 			engine: spec.engine.clone(),
 			io_channel: RwLock::new(None),
@@ -1347,6 +1353,7 @@ mod tests {
 				infinite_pending_block: false,
 				tx_queue_penalization: Penalization::Disabled,
 				tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+				tx_queue_replace_bump: pool::scoring::DEFAULT_REPLACE_BUMP,
 				tx_queue_no_unfamiliar_locals: false,
 				refuse_service_transactions: false,
 				pool_limits: Default::default(),
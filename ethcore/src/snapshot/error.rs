@@ -59,6 +59,8 @@ pub enum Error {
 	ChunkTooSmall,
 	/// Oversized chunk
 	ChunkTooLarge,
+	/// Chunk contents don't match the hash they were given under.
+	ChunkHashMismatch(H256, H256),
 	/// Snapshots not supported by the consensus engine.
 	SnapshotsUnsupported,
 	/// Bad epoch transition.
@@ -88,6 +90,7 @@ impl fmt::Display for Error {
 			Error::VersionNotSupported(ref ver) => write!(f, "Snapshot version {} is not supprted.", ver),
 			Error::ChunkTooSmall => write!(f, "Chunk size is too small."),
 			Error::ChunkTooLarge => write!(f, "Chunk size is too large."),
+			Error::ChunkHashMismatch(ref expected, ref found) => write!(f, "Chunk has wrong hash. Expected {:?}, got {:?}", expected, found),
 			Error::SnapshotsUnsupported => write!(f, "Snapshots unsupported by consensus engine."),
 			Error::BadEpochProof(i) => write!(f, "Bad epoch proof for transition to epoch {}", i),
 			Error::WrongChunkFormat(ref msg) => write!(f, "Wrong chunk format: {}", msg),
@@ -353,7 +353,7 @@ impl StateRebuilder {
 		for (code_hash, code, first_with) in status.new_code {
 			for addr_hash in self.missing_code.remove(&code_hash).unwrap_or_else(Vec::new) {
 				let mut db = AccountDBMut::from_hash(self.db.as_hashdb_mut(), addr_hash);
-				db.emplace(code_hash, DBValue::from_slice(&code));
+				journaldb::emplace_precomputed(&mut db, code_hash, DBValue::from_slice(&code))?;
 			}
 
 			self.known_code.insert(code_hash, first_with);
@@ -454,7 +454,7 @@ fn rebuild_accounts(
 									.ok_or_else(|| Error::MissingCode(vec![first_with]))?;
 
 								// and write it again under a different mangled key
-								AccountDBMut::from_hash(db, hash).emplace(code_hash, code);
+								journaldb::emplace_precomputed(&mut AccountDBMut::from_hash(db, hash), code_hash, code)?;
 							}
 							// if not, queue it up to be filled later
 							None => status.missing_code.push((hash, code_hash)),
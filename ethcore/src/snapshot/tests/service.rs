@@ -146,3 +146,63 @@ fn guards_delete_folders() {
 	assert!(!path.join("db").exists());
 	assert!(path.join("temp").exists());
 }
+
+// take a snapshot of a short chain, returning the service holding it.
+fn snapshot_service(tempdir: &TempDir) -> Service {
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, 20, 5, &[1.into(), 2.into()]);
+	let spec = Spec::new_null();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		restoration_db_handler: restoration_db_handler(DatabaseConfig::with_columns(::db::NUM_COLUMNS)),
+		pruning: ::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: tempdir.path().join("snapshot"),
+		db_restore: Arc::new(NoopDBRestore),
+	};
+
+	let service = Service::new(service_params).unwrap();
+	service.take_snapshot(&client, 20).unwrap();
+	service
+}
+
+#[test]
+fn rejects_corrupted_chunk() {
+	let tempdir = TempDir::new("").unwrap();
+	let service = snapshot_service(&tempdir);
+	let manifest = service.manifest().unwrap();
+
+	service.init_restore(manifest.clone(), false).unwrap();
+
+	let hash = manifest.state_hashes[0];
+	let mut chunk = service.chunk(hash).unwrap();
+	let last = chunk.len() - 1;
+	chunk[last] ^= 1;
+	service.feed_state_chunk(hash, &chunk);
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Failed);
+	assert!(!tempdir.path().join("snapshot").join("restoration").exists());
+}
+
+#[test]
+fn rejects_wrong_state_root() {
+	let tempdir = TempDir::new("").unwrap();
+	let service = snapshot_service(&tempdir);
+	let mut manifest = service.manifest().unwrap();
+	manifest.state_root = Default::default();
+
+	service.init_restore(manifest.clone(), false).unwrap();
+
+	for &hash in &manifest.state_hashes {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_state_chunk(hash, &chunk);
+	}
+
+	for &hash in &manifest.block_hashes {
+		let chunk = service.chunk(hash).unwrap();
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Failed);
+	assert!(!tempdir.path().join("snapshot").join("restoration").exists());
+}
@@ -607,7 +607,14 @@ impl Spec {
 
 	// given a pre-constructor state, run all the given constructors and produce a new state and
 	// state root.
-	fn run_constructors<T: Backend>(&self, factories: &Factories, mut db: T) -> Result<T, Error> {
+	fn run_constructors<T: Backend>(&self, factories: &Factories, db: T) -> Result<T, Error> {
+		let (root, db) = self.build_genesis_state(factories, db)?;
+		*self.state_root_memo.write() = root;
+		Ok(db)
+	}
+
+	// like `run_constructors`, but leaves the memoized state root alone.
+	fn build_genesis_state<T: Backend>(&self, factories: &Factories, mut db: T) -> Result<(H256, T), Error> {
 		let mut root = KECCAK_NULL_RLP;
 
 		// basic accounts in spec.
@@ -686,8 +693,7 @@ impl Spec {
 			state.drop()
 		};
 
-		*self.state_root_memo.write() = root;
-		Ok(db)
+		Ok((root, db))
 	}
 
 	/// Return the state root for the genesis state, memoising accordingly.
@@ -807,7 +813,13 @@ impl Spec {
 
 		// TODO: could optimize so we don't re-run, but `ensure_db_good` is barely ever
 		// called anyway.
-		let db = self.run_constructors(factories, db)?;
+		// the memoized root may come straight from the `stateRoot` of the spec.
+		let expected_root = self.state_root();
+		let (root, db) = self.build_genesis_state(factories, db)?;
+		if root != expected_root {
+			return Err(format!("Genesis state root {:?} does not match the stateRoot {:?} given in the chain spec.", root, expected_root).into());
+		}
+
 		Ok(db)
 	}
 
@@ -1036,4 +1048,28 @@ mod tests {
 		assert_eq!(state.storage_at(&address, &H256::zero()).unwrap(), expected);
 		assert_eq!(state.balance(&address).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn rejects_wrong_genesis_state_root() {
+		let tempdir = TempDir::new("").unwrap();
+		let json = String::from_utf8(include_bytes!("../../res/null.json").to_vec()).unwrap();
+		let json = json.replacen("\"genesis\": {", "\"genesis\": {\n\t\t\"stateRoot\": \"0x0000000000000000000000000000000000000000000000000000000000000001\",", 1);
+		let spec = Spec::load(&tempdir.path(), json.as_bytes()).unwrap();
+
+		// a failed check doesn't replace the root it checked against.
+		assert!(spec.ensure_db_good(get_temp_state_db(), &Default::default()).is_err());
+		assert_eq!(spec.state_root(), H256::from(1));
+		assert!(spec.ensure_db_good(get_temp_state_db(), &Default::default()).is_err());
+	}
+
+	#[test]
+	fn accepts_right_genesis_state_root_twice() {
+		let spec = Spec::new_test_constructor();
+		let root = spec.state_root();
+		for _ in 0..2 {
+			let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+			assert!(db.as_hashdb().contains(&root));
+			assert_eq!(spec.state_root(), root);
+		}
+	}
 }
@@ -38,6 +38,7 @@ use pod_state::{self, PodState};
 use types::basic_account::BasicAccount;
 use executed::{Executed, ExecutionError};
 use types::state_diff::StateDiff;
+use types::state_override::StateOverride;
 use transaction::SignedTransaction;
 use state_db::StateDB;
 use factory::VmFactory;
@@ -790,6 +791,32 @@ impl<B: Backend> State<B> {
 		Ok(())
 	}
 
+	/// Apply `overrides` to the accounts they name, creating those which don't exist.
+	/// Used to make calls on a modified state; the changes are never meant to be committed.
+	pub fn override_accounts(&mut self, overrides: &StateOverride) -> TrieResult<()> {
+		for (address, account) in overrides {
+			{
+				let mut entry = self.require(address, false)?;
+				if let Some(balance) = account.balance {
+					entry.set_balance(balance);
+				}
+				if let Some(nonce) = account.nonce {
+					entry.set_nonce(nonce);
+				}
+			}
+
+			if let Some(ref code) = account.code {
+				self.reset_code(address, code.clone())?;
+			}
+
+			for (key, value) in &account.storage {
+				self.set_storage(address, *key, *value)?;
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Execute a given transaction, producing a receipt and an optional trace.
 	/// This will change the state accordingly.
 	pub fn apply(&mut self, env_info: &EnvInfo, machine: &Machine, t: &SignedTransaction, tracing: bool) -> ApplyResult<FlatTrace, VMTrace> {
@@ -2462,6 +2489,34 @@ mod tests {
 		assert_eq!(state.storage_at(&a, &k).unwrap(), H256::from(U256::from(1)));
 	}
 
+	#[test]
+	fn proofs_verify_against_state_root() {
+		use ethtrie::verify_proof;
+
+		let mut state = get_temp_state();
+		let a: Address = 0xa.into();
+		let key: H256 = 0xb.into();
+		state.add_balance(&a, &256.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&a, key, 0xc.into()).unwrap();
+		state.add_balance(&0xd.into(), &1.into(), CleanupMode::NoEmpty).unwrap();
+		state.commit().unwrap();
+
+		let (proof, account) = state.prove_account(keccak(&a)).unwrap();
+		assert_eq!(account.balance, 256.into());
+		let value = verify_proof(state.root(), &keccak(&a), &proof).unwrap().unwrap();
+		assert_eq!(::rlp::decode::<BasicAccount>(&value).unwrap(), account);
+
+		let (proof, value) = state.prove_storage(keccak(&a), keccak(&key)).unwrap();
+		assert_eq!(value, 0xc.into());
+		let value = verify_proof(&account.storage_root, &keccak(&key), &proof).unwrap().unwrap();
+		assert_eq!(::rlp::decode::<U256>(&value).unwrap(), 0xc.into());
+
+		let missing: Address = 0xe.into();
+		let (proof, account) = state.prove_account(keccak(&missing)).unwrap();
+		assert_eq!(account.balance, 0.into());
+		assert_eq!(verify_proof(state.root(), &keccak(&missing), &proof).unwrap(), None);
+	}
+
 	#[test]
 	fn create_empty() {
 		let mut state = get_temp_state();
@@ -2592,4 +2647,37 @@ mod tests {
 						   .into_iter().collect(),
 				   })).as_ref());
 	}
+
+	#[test]
+	fn override_accounts_creates_and_changes_accounts() {
+		use types::state_override::AccountOverride;
+
+		let a = Address::from(0x42);
+		let b = Address::from(0x43);
+		let mut state = get_temp_state();
+		state.add_balance(&b, &100.into(), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&b, H256::from(1), H256::from(2)).unwrap();
+
+		let mut overrides = StateOverride::new();
+		overrides.insert(a, AccountOverride {
+			balance: Some(5.into()),
+			nonce: Some(7.into()),
+			code: Some(vec![0x60, 0x00]),
+			storage: Default::default(),
+		});
+		overrides.insert(b, AccountOverride {
+			balance: Some(3.into()),
+			storage: vec![(H256::from(3), H256::from(4))].into_iter().collect(),
+			..Default::default()
+		});
+		state.override_accounts(&overrides).unwrap();
+
+		assert!(state.exists(&a).unwrap());
+		assert_eq!(state.balance(&a).unwrap(), 5.into());
+		assert_eq!(state.nonce(&a).unwrap(), 7.into());
+		assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![0x60, 0x00])));
+		assert_eq!(state.balance(&b).unwrap(), 3.into());
+		assert_eq!(state.storage_at(&b, &H256::from(1)).unwrap(), H256::from(2));
+		assert_eq!(state.storage_at(&b, &H256::from(3)).unwrap(), H256::from(4));
+	}
 }
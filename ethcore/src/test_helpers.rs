@@ -101,6 +101,19 @@ pub fn generate_dummy_client(block_number: u32) -> Arc<Client> {
 	generate_dummy_client_with_spec_and_data(Spec::new_test, block_number, 0, &[])
 }
 
+/// Generates dummy client (not test client) with the genesis of `spec` only, optionally keeping a fat state database
+pub fn generate_dummy_client_with_fat_db(spec: &Spec, fat_db: bool) -> Arc<Client> {
+	let mut config = ClientConfig::default();
+	config.fat_db = fat_db;
+	Client::new(
+		config,
+		spec,
+		new_db(),
+		Arc::new(Miner::new_for_tests(spec, None)),
+		IoChannel::disconnected(),
+	).unwrap()
+}
+
 /// Generates dummy client (not test client) with corresponding amount of blocks and txs per every block
 pub fn generate_dummy_client_with_data(block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> Arc<Client> {
 	generate_dummy_client_with_spec_and_data(Spec::new_null, block_number, txs_per_block, tx_gas_prices)
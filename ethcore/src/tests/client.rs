@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fs;
 use std::str::FromStr;
 use std::sync::Arc;
 use hash::keccak;
@@ -21,20 +22,23 @@ use io::IoChannel;
 use client::{BlockChainClient, Client, ClientConfig, BlockId, ChainInfo, BlockInfo, PrepareOpenBlock, ImportSealedBlock, ImportBlock};
 use state::{self, State, CleanupMode};
 use executive::{Executive, TransactOptions};
+use header::Header;
 use ethereum;
 use block::IsBlock;
 use test_helpers::{
 	generate_dummy_client, push_blocks_to_client, get_test_client_with_blocks, get_good_dummy_block_seq,
-	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block
+	generate_dummy_client_with_data, generate_dummy_client_with_fat_db, get_good_dummy_block, get_bad_state_dummy_block
 };
 use types::filter::Filter;
-use ethereum_types::{U256, Address};
+use error::{BlockImportError, BlockImportErrorKind, QueueErrorKind};
+use ethereum_types::{H256, U256, Address};
 use miner::{Miner, PendingOrdering};
 use spec::Spec;
 use views::BlockView;
 use ethkey::KeyPair;
 use transaction::{PendingTransaction, Transaction, Action, Condition};
 use miner::MinerService;
+use serde_json;
 use tempdir::TempDir;
 use test_helpers;
 use verification::queue::kind::blocks::Unverified;
@@ -131,6 +135,48 @@ fn query_bad_block() {
 	assert!(bad_block.is_none());
 }
 
+#[test]
+fn reports_bad_block_without_touching_state() {
+	let spec = Spec::new_test_with_reward();
+	let genesis = spec.genesis_header();
+	let reports = TempDir::new("").unwrap();
+	let mut config = ClientConfig::default();
+	config.bad_block_reports = Some(reports.path().to_owned());
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let mut header = Header::new();
+	header.set_gas_limit(*genesis.gas_limit());
+	header.set_difficulty(0x20000.into());
+	header.set_timestamp(40);
+	header.set_number(1);
+	header.set_parent_hash(genesis.hash());
+	header.set_state_root(0xbad.into());
+	client.import_block(Unverified::from_rlp(test_helpers::create_test_block(&header)).unwrap()).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+	assert_eq!(client.chain_info().best_block_number, 0);
+
+	let paths: Vec<_> = fs::read_dir(reports.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+	assert_eq!(paths, vec![reports.path().join(format!("1-{:x}.json", header.hash()))]);
+	let report: serde_json::Value = serde_json::from_reader(fs::File::open(&paths[0]).unwrap()).unwrap();
+	assert_eq!(report["expected"]["stateRoot"], json!(format!("0x{:x}", H256::from(0xbad))));
+	// the block reward went to the author.
+	assert_eq!(report["accounts"].as_array().map(Vec::len), Some(1));
+
+	// the state the block was enacted into was thrown away with it.
+	let computed_root = H256::from_str(&report["computed"]["stateRoot"].as_str().unwrap()[2..]).unwrap();
+	assert!(computed_root != *genesis.state_root());
+	assert!(client.state_data(&computed_root).is_none());
+	assert!(client.state_data(genesis.state_root()).is_some());
+	assert_eq!(client.state_at(BlockId::Latest).map(|s| *s.root()), Some(*genesis.state_root()));
+}
+
 #[test]
 fn returns_chain_info() {
 	let dummy_block = get_good_dummy_block();
@@ -188,6 +234,50 @@ fn imports_block_sequence() {
 	assert!(!block.into_inner().is_empty());
 }
 
+#[test]
+fn rejects_blocks_while_queue_is_full() {
+	let spec = Spec::new_test();
+	let mut config = ClientConfig::default();
+	config.queue.max_mem_use = 0; // raised to the minimum limit, about one block more than an empty queue uses
+	let client = Client::new(
+		config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let mut rejected = 0;
+	for block in get_good_dummy_block_seq(50) {
+		let mut attempts = 0;
+		loop {
+			attempts += 1;
+			assert!(attempts <= 10, "block still rejected after draining the queue");
+
+			// a block is only taken while the queue is under its memory limit.
+			let before = client.queue_info();
+			match client.import_block(Unverified::from_rlp(block.clone()).unwrap()) {
+				Ok(_) => {
+					assert!(before.mem_used <= before.max_mem_use);
+					break;
+				},
+				Err(BlockImportError(BlockImportErrorKind::Queue(QueueErrorKind::Full(_)), _)) => rejected += 1,
+				Err(e) => panic!("unexpected import error: {:?}", e),
+			}
+			assert!(client.queue_info().is_full());
+			// nothing drains the queue with a disconnected channel, so it only empties when we import.
+			client.flush_queue();
+			client.import_verified_blocks();
+			assert!(client.queue_info().mem_used <= client.queue_info().max_mem_use);
+		}
+	}
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert!(rejected > 0);
+	assert_eq!(client.chain_info().best_block_number, 50);
+}
+
 #[test]
 fn can_collect_garbage() {
 	let client = generate_dummy_client(100);
@@ -365,3 +455,131 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn lists_storage_keys_of_fat_db() {
+	use std::collections::{BTreeMap, BTreeSet};
+	use ethereum_types::H256;
+	use pod_account::PodAccount;
+	use pod_state::PodState;
+
+	let contract = Address::from(0x42);
+	let storage: BTreeMap<H256, H256> = (1..101u64).map(|i| (H256::from(i), H256::from(i * 2))).collect();
+
+	let mut spec = Spec::new_test();
+	let mut accounts = BTreeMap::new();
+	accounts.insert(contract, PodAccount {
+		balance: 0.into(),
+		nonce: 0.into(),
+		code: Some(vec![0x60, 0x00]),
+		storage: storage.clone(),
+	});
+	spec.set_genesis_state(PodState::from(accounts)).unwrap();
+
+	let client = generate_dummy_client_with_fat_db(&spec, true);
+	let all = client.list_storage(BlockId::Latest, &contract, None, 1000).unwrap();
+	assert_eq!(all.iter().cloned().collect::<BTreeSet<_>>(), storage.keys().cloned().collect());
+
+	// paging through the keys visits each of them exactly once
+	let first = client.list_storage(BlockId::Latest, &contract, None, 40).unwrap();
+	let rest = client.list_storage(BlockId::Latest, &contract, first.last(), 1000).unwrap();
+	assert_eq!(first.len(), 40);
+	assert_eq!(first.into_iter().chain(rest).collect::<Vec<_>>(), all);
+
+	assert_eq!(generate_dummy_client_with_fat_db(&spec, false).list_storage(BlockId::Latest, &contract, None, 1000), None);
+}
+
+#[test]
+fn state_diff_compares_state_tries() {
+	use ethereum_types::H256;
+	use types::account_diff::Diff;
+
+	let spec = Spec::new_test();
+	let client = generate_dummy_client_with_fat_db(&spec, true);
+	let address = Address::from(0x42);
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+	b.block_mut().state_mut().add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().state_mut().set_storage(&address, H256::from(1), H256::from(2)).unwrap();
+	b.block_mut().state_mut().commit().unwrap();
+	let b = b.close_and_lock().unwrap().seal(&*spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let diff = client.state_diff(BlockId::Latest).unwrap();
+	let account = &diff.get()[&address];
+	assert_eq!(account.balance, Diff::Born(5.into()));
+	assert_eq!(account.code, Diff::Born(vec![]));
+	assert_eq!(account.storage.len(), 1);
+	assert_eq!(account.storage[&H256::from(1)], Diff::Born(H256::from(2)));
+
+	// the genesis block is compared with itself
+	assert!(client.state_diff(BlockId::Number(0)).unwrap().get().is_empty());
+
+	assert_eq!(generate_dummy_client_with_fat_db(&spec, false).state_diff(BlockId::Latest), None);
+}
+
+#[test]
+fn calls_see_overridden_state() {
+	use std::collections::BTreeMap;
+	use client::Call;
+	use ethereum_types::H256;
+	use pod_account::PodAccount;
+	use pod_state::PodState;
+	use types::state_override::{AccountOverride, StateOverride};
+
+	// returns storage slot 0.
+	let code = vec![0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+	let existing = Address::from(0x42);
+	let created = Address::from(0x43);
+
+	let mut spec = Spec::new_test();
+	let mut accounts = BTreeMap::new();
+	accounts.insert(existing, PodAccount {
+		balance: 0.into(),
+		nonce: 0.into(),
+		code: Some(code.clone()),
+		storage: vec![(H256::from(0), H256::from(1))].into_iter().collect(),
+	});
+	spec.set_genesis_state(PodState::from(accounts)).unwrap();
+
+	let client = Client::new(
+		ClientConfig::default(),
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+	let header = client.best_block_header();
+
+	let call = |to, overrides: &StateOverride| {
+		let transaction = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(to),
+			value: 0.into(),
+			data: Vec::new(),
+		}.fake_sign(Address::default());
+
+		let mut state = client.latest_state();
+		client.override_state(&mut state, overrides).unwrap();
+		client.call(&transaction, Default::default(), &mut state, &header).unwrap().output
+	};
+
+	assert_eq!(call(existing, &StateOverride::new()), H256::from(1).to_vec());
+
+	let mut overrides = StateOverride::new();
+	overrides.insert(existing, AccountOverride {
+		storage: vec![(H256::from(0), H256::from(2))].into_iter().collect(),
+		..Default::default()
+	});
+	overrides.insert(created, AccountOverride {
+		code: Some(code.clone()),
+		storage: vec![(H256::from(0), H256::from(3))].into_iter().collect(),
+		..Default::default()
+	});
+	assert_eq!(call(existing, &overrides), H256::from(2).to_vec());
+	assert_eq!(call(created, &overrides), H256::from(3).to_vec());
+
+	// the overrides are not written to the chain state.
+	assert_eq!(call(created, &StateOverride::new()), Vec::<u8>::new());
+}
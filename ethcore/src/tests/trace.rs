@@ -20,6 +20,7 @@ use ethkey::KeyPair;
 use hash::keccak;
 use block::*;
 use ethereum_types::{U256, Address};
+use executive::contract_address;
 use io::*;
 use spec::*;
 use client::*;
@@ -31,8 +32,10 @@ use miner::Miner;
 use transaction::{Action, Transaction};
 use views::BlockView;
 use trace::{RewardType, LocalizedTrace};
-use trace::trace::Action::Reward;
+use trace::trace::Action::{Call, Reward};
 use test_helpers;
+use rustc_hex::FromHex;
+use vm::CreateContractAddress;
 use verification::queue::kind::blocks::Unverified;
 
 #[test]
@@ -207,3 +210,60 @@ fn can_trace_block_and_uncle_reward() {
 	let traces = client.block_traces(BlockId::Number(3));
 	assert_eq!(traces.unwrap().len(), 3);
 }
+
+#[test]
+fn can_filter_internal_call_traces_by_address() {
+	let spec = Spec::new_null();
+	let mut client_config = ClientConfig::default();
+	client_config.tracing.enabled = true;
+	let client = Client::new(
+		client_config,
+		&spec,
+		test_helpers::new_db(),
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let kp = KeyPair::from_secret_slice(&keccak("")).unwrap();
+	let callee: Address = "000000000000000000000000000000000000dead".into();
+	let (contract, _) = contract_address(CreateContractAddress::FromSenderAndNonce, &kp.address(), &0.into(), &[]);
+
+	// init code returning a runtime code which calls `callee` with all the gas left.
+	let code = "6022600c60003960226000f3\
+		6000600060006000600073000000000000000000000000000000000000dead5af100".from_hex().unwrap();
+	let create = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100000.into(),
+		action: Action::Create,
+		data: code,
+		value: U256::zero(),
+	}.sign(kp.secret(), Some(spec.network_id()));
+	let call = Transaction {
+		nonce: 1.into(),
+		gas_price: 0.into(),
+		gas: 100000.into(),
+		action: Action::Call(contract),
+		data: vec![],
+		value: U256::zero(),
+	}.sign(kp.secret(), Some(spec.network_id()));
+	test_helpers::push_block_with_transactions(&client, &[create, call]);
+
+	let filter = |from_address, to_address| client.filter_traces(TraceFilter {
+		range: (BlockId::Number(1)..BlockId::Number(1)),
+		from_address,
+		to_address,
+		after: None,
+		count: None,
+	}).expect("tracing is enabled; qed");
+	let calls = |traces: Vec<LocalizedTrace>| traces.into_iter().map(|trace| match trace.action {
+		Call(call) => (call.from, call.to, trace.trace_address),
+		action => panic!("unexpected trace: {:?}", action),
+	}).collect::<Vec<_>>();
+
+	assert_eq!(calls(filter(vec![], vec![callee])), vec![(contract, callee, vec![0])]);
+	assert_eq!(calls(filter(vec![contract], vec![])), vec![(contract, callee, vec![0])]);
+	// both the contract creation and the call into it go from the sender to the contract.
+	assert_eq!(filter(vec![kp.address()], vec![contract]).len(), 2);
+	assert!(filter(vec![callee], vec![]).is_empty());
+}
@@ -19,7 +19,7 @@
 ///
 
 use std::collections::{HashSet, VecDeque};
-use std::cmp;
+use std::{cmp, iter};
 use heapsize::HeapSizeOf;
 use ethereum_types::H256;
 use rlp::{self, Rlp};
@@ -27,7 +27,7 @@ use ethcore::header::BlockNumber;
 use ethcore::client::{BlockStatus, BlockId, BlockImportError, BlockImportErrorKind};
 use ethcore::error::{ImportErrorKind, QueueErrorKind, BlockError};
 use sync_io::SyncIo;
-use blocks::{BlockCollection, SyncBody, SyncHeader};
+use blocks::{BlockAndReceipts, BlockCollection, SyncBody, SyncHeader};
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
 const MAX_BODIES_TO_REQUEST: usize = 32;
@@ -468,13 +468,11 @@ impl BlockDownloader {
 		let mut imported = HashSet::new();
 		let blocks = self.blocks.drain();
 		let count = blocks.len();
-		for block_and_receipts in blocks {
-			let block = block_and_receipts.block;
-			let receipts = block_and_receipts.receipts;
-
-			let h = block.header.hash();
-			let number = block.header.number();
-			let parent = *block.header.parent_hash();
+		let mut blocks = blocks.into_iter();
+		while let Some(block_and_receipts) = blocks.next() {
+			let h = block_and_receipts.block.header.hash();
+			let number = block_and_receipts.block.header.number();
+			let parent = *block_and_receipts.block.header.parent_hash();
 
 			if self.target_hash.as_ref().map_or(false, |t| t == &h) {
 				self.state = State::Complete;
@@ -482,6 +480,14 @@ impl BlockDownloader {
 				return Ok(());
 			}
 
+			// importing consumes the block, so check for room before handing it over.
+			if block_and_receipts.receipts.is_none() && io.chain().queue_info().is_full() {
+				debug!(target: "sync", "Block import queue full, keeping {} blocks for later", blocks.len() + 1);
+				self.blocks.restore(iter::once(block_and_receipts).chain(blocks.by_ref()).collect());
+				break;
+			}
+
+			let BlockAndReceipts { block, receipts } = block_and_receipts;
 			let result = if let Some(receipts) = receipts {
 				io.chain().queue_ancient_block(block, receipts)
 			} else {
@@ -549,4 +555,44 @@ impl BlockDownloader {
 	}
 }
 
-//TODO: module tests
+#[cfg(test)]
+mod tests {
+	use std::collections::VecDeque;
+	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockChainClient, BlockId, ChainInfo};
+	use parking_lot::RwLock;
+	use rlp::Rlp;
+	use tests::helpers::TestIo;
+	use tests::snapshot::TestSnapshotService;
+	use blocks::SyncHeader;
+	use super::{BlockDownloader, State};
+
+	#[test]
+	fn keeps_blocks_while_import_queue_is_full() {
+		let source = TestBlockChainClient::new();
+		source.add_blocks(10, EachBlockWith::Nothing);
+		let headers: Vec<_> = (1..11).map(|n| {
+			let block = (&source as &BlockChainClient).block(BlockId::Number(n)).unwrap().into_inner();
+			SyncHeader::from_rlp(Rlp::new(&block).at(0).unwrap().as_raw().to_vec()).unwrap()
+		}).collect();
+
+		let client = TestBlockChainClient::new();
+		let ss = TestSnapshotService::new();
+		let queue = RwLock::new(VecDeque::new());
+		let mut io = TestIo::new(&client, &ss, &queue, None);
+
+		let genesis = client.chain_info().genesis_hash;
+		let mut downloader = BlockDownloader::new(false, &genesis, 0);
+		downloader.reset_to(vec![headers[0].header.hash()]);
+		downloader.blocks.insert_headers(headers);
+
+		client.set_queue_size(1);
+		assert_eq!(downloader.collect_blocks(&mut io, false), Ok(()));
+		assert_eq!(client.chain_info().best_block_number, 0);
+		assert_eq!(downloader.state, State::Blocks);
+
+		client.set_queue_size(0);
+		assert_eq!(downloader.collect_blocks(&mut io, false), Ok(()));
+		assert_eq!(client.chain_info().best_block_number, 10);
+		assert_eq!(downloader.state, State::Idle);
+	}
+}
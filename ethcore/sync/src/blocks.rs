@@ -390,6 +390,42 @@ impl BlockCollection {
 		drained
 	}
 
+	/// Put back the tail of a chain returned by `drain` which could not be imported, so that
+	/// the next `drain` returns it again.
+	pub fn restore(&mut self, blocks: Vec<BlockAndReceipts>) {
+		let head = match blocks.first() {
+			Some(first) => *first.block.header.parent_hash(),
+			None => return,
+		};
+
+		for BlockAndReceipts { block, receipts } in blocks {
+			let hash = block.header.hash();
+			let (header_bytes, body) = {
+				let rlp = Rlp::new(&block.bytes);
+				let raw = |i| rlp.at(i).expect("block RLP is assembled by drain; qed").as_raw().to_vec();
+				(raw(0), SyncBody {
+					transactions_bytes: raw(1),
+					transactions: block.transactions,
+					uncles_bytes: raw(2),
+					uncles: block.uncles,
+				})
+			};
+			let receipts_root = match self.need_receipts {
+				true => *block.header.receipts_root(),
+				false => H256::new(),
+			};
+			self.blocks.insert(hash, SyncBlock {
+				header: SyncHeader { bytes: header_bytes, header: block.header },
+				body: Some(body),
+				receipts,
+				receipts_root,
+			});
+		}
+
+		trace!(target: "sync", "Restored blocks, new head :{:?}", head);
+		self.head = Some(head);
+	}
+
 	/// Check if the collection is empty. We consider the syncing round complete once
 	/// there is no block data left and only a single or none head pointer remains.
 	pub fn is_empty(&self) -> bool {
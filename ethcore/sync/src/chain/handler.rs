@@ -198,6 +198,9 @@ impl SyncHandler {
 				unknown = true;
 				trace!(target: "sync", "New block with unknown parent ({:?}) {:?}", p, hash);
 			},
+			Err(BlockImportError(BlockImportErrorKind::Queue(QueueErrorKind::Full(limit)), _)) => {
+				debug!(target: "sync", "Block import queue full ({}), ignoring new block {:?}", limit, hash);
+			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", hash, e);
 				return Err(DownloaderImportError::Invalid);
@@ -946,7 +946,7 @@ impl ChainSync {
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
 		match self.state {
-			SyncState::Waiting if !io.chain().queue_info().is_full() => {
+			SyncState::Waiting if io.chain().queue_info().is_drained() => {
 				self.state = SyncState::Blocks;
 				self.continue_sync(io);
 			},
@@ -1289,6 +1289,29 @@ pub mod tests {
 		assert_eq!(1, lagging_peers.len());
 	}
 
+	#[test]
+	fn resumes_only_after_queue_drains() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		client.set_queue_limit(10);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		io.chain.set_queue_size(11);
+		sync.pause_sync();
+
+		// no longer full, but still above the low-water mark.
+		io.chain.set_queue_size(8);
+		sync.check_resume(&mut io);
+		assert_eq!(sync.state, SyncState::Waiting);
+
+		io.chain.set_queue_size(5);
+		sync.check_resume(&mut io);
+		assert!(sync.state != SyncState::Waiting);
+	}
+
 	#[test]
 	fn calculates_tree_for_lagging_peer() {
 		let mut client = TestBlockChainClient::new();
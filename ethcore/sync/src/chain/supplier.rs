@@ -136,16 +136,19 @@ impl SyncSupplier {
 		let mut data = Bytes::new();
 		let inc = skip.saturating_add(1) as BlockNumber;
 		let overlay = io.chain_overlay().read();
+		// one hash for each number visited below, as far as the canonical chain goes.
+		let mut hashes = io.chain().block_hashes_with_skip(number, max_count, skip as u64, reverse).into_iter();
 
 		// We are checking the `overlay` as well since it's where the ForkBlock
 		// header is cached : so peers can confirm we are on the right fork,
 		// even if we are not synced until the fork block
 		while (number <= last || overlay.contains_key(&number)) && count < max_count {
+			let hash = hashes.next();
 			if let Some(hdr) = overlay.get(&number) {
 				trace!(target: "sync", "{}: Returning cached fork header", peer_id);
 				data.extend_from_slice(hdr);
 				count += 1;
-			} else if let Some(hdr) = io.chain().block_header(BlockId::Number(number)) {
+			} else if let Some(hdr) = hash.and_then(|hash| io.chain().block_header(BlockId::Hash(hash))) {
 				data.append(&mut hdr.into_inner());
 				count += 1;
 			} else {
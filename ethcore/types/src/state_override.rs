@@ -0,0 +1,37 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Overrides of the state a call is made on.
+
+use std::collections::BTreeMap;
+use ethereum_types::{H256, U256, Address};
+use bytes::Bytes;
+
+/// Changes made to an account before making a call.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AccountOverride {
+	/// The balance the account should have.
+	pub balance: Option<U256>,
+	/// The nonce the account should have.
+	pub nonce: Option<U256>,
+	/// The code the account should have.
+	pub code: Option<Bytes>,
+	/// Storage slots to set; the other slots keep their values.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Accounts to change before making a call, each created if it doesn't exist.
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
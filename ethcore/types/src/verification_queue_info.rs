@@ -46,6 +46,12 @@ impl VerificationQueueInfo {
 			self.mem_used > self.max_mem_use
 	}
 
+	/// Indicates that a full queue has drained enough to take more items:
+	/// it is empty, or back under half of both limits.
+	pub fn is_drained(&self) -> bool {
+		self.is_empty() || (self.total_queue_size() <= self.max_queue_size / 2 && self.mem_used <= self.max_mem_use / 2)
+	}
+
 	/// Indicates that queue is empty
 	pub fn is_empty(&self) -> bool {
 		self.unverified_queue_size + self.verified_queue_size + self.verifying_queue_size == 0
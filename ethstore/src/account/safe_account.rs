@@ -125,33 +125,44 @@ impl SafeAccount {
 		})
 	}
 
+	/// Decrypt the account's secret, making sure it belongs to the account's address.
+	pub fn secret(&self, password: &Password) -> Result<Secret, Error> {
+		let secret = self.crypto.secret(password)?;
+		let address = KeyPair::from_secret(secret.clone())?.address();
+		if address != self.address {
+			return Err(Error::InvalidKeyFile(format!("Key file is for address {:?}, but its secret belongs to {:?}", self.address, address)));
+		}
+
+		Ok(secret)
+	}
+
 	/// Sign a message.
 	pub fn sign(&self, password: &Password, message: &Message) -> Result<Signature, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		sign(&secret, message).map_err(From::from)
 	}
 
 	/// Decrypt a message.
 	pub fn decrypt(&self, password: &Password, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		ethkey::crypto::ecies::decrypt(&secret, shared_mac, message).map_err(From::from)
 	}
 
 	/// Agree on shared key.
 	pub fn agree(&self, password: &Password, other: &Public) -> Result<Secret, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		agree(&secret, other).map_err(From::from)
 	}
 
 	/// Derive public key.
 	pub fn public(&self, password: &Password) -> Result<Public, Error> {
-		let secret = self.crypto.secret(password)?;
+		let secret = self.secret(password)?;
 		Ok(KeyPair::from_secret(secret)?.public().clone())
 	}
 
 	/// Change account's password.
 	pub fn change_password(&self, old_password: &Password, new_password: &Password, iterations: u32) -> Result<Self, Error> {
-		let secret = self.crypto.secret(old_password)?;
+		let secret = self.secret(old_password)?;
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
@@ -173,6 +184,7 @@ impl SafeAccount {
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random, verify_public, Message};
+	use Error;
 	use super::SafeAccount;
 
 	#[test]
@@ -199,4 +211,19 @@ mod tests {
 		assert!(new_account.sign(&first_password, &message).is_err());
 		assert!(new_account.sign(&sec_password, &message).is_ok());
 	}
+
+	#[test]
+	fn reject_secret_of_different_address() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world".into();
+		let mut account = SafeAccount::create(&keypair, [0u8; 16], &password, 10240, "Test".to_owned(), "{}".to_owned()).unwrap();
+		assert_eq!(account.secret(&password).unwrap(), *keypair.secret());
+
+		account.address = Random.generate().unwrap().address();
+		match account.secret(&password) {
+			Err(Error::InvalidKeyFile(_)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		assert!(account.sign(&password, &Message::default()).is_err());
+	}
 }
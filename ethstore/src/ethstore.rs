@@ -156,7 +156,7 @@ impl SimpleSecretStore for EthStore {
 
 impl SecretStore for EthStore {
 	fn raw_secret(&self, account: &StoreAccountRef, password: &Password) -> Result<OpaqueSecret, Error> {
-		Ok(OpaqueSecret(self.get(account)?.crypto.secret(password)?))
+		Ok(OpaqueSecret(self.get(account)?.secret(password)?))
 	}
 
 	fn import_presale(&self, vault: SecretVaultRef, json: &[u8], password: &Password) -> Result<StoreAccountRef, Error> {
@@ -174,8 +174,8 @@ impl SecretStore for EthStore {
 			safe_account.id = Random::random();
 		}
 
-		let secret = safe_account.crypto.secret(password).map_err(|_| Error::InvalidPassword)?;
-		safe_account.address = KeyPair::from_secret(secret)?.address();
+		// also verifies that the file's address matches its secret
+		safe_account.secret(password)?;
 		self.store.import(vault, safe_account)
 	}
 
@@ -186,7 +186,7 @@ impl SecretStore for EthStore {
 
 	fn copy_account(&self, new_store: &SimpleSecretStore, new_vault: SecretVaultRef, account: &StoreAccountRef, password: &Password, new_password: &Password) -> Result<(), Error> {
 		let account = self.get(account)?;
-		let secret = account.crypto.secret(password)?;
+		let secret = account.secret(password)?;
 		new_store.insert_account(new_vault, secret, new_password)?;
 		Ok(())
 	}
@@ -467,7 +467,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.secret(password)?, derivation)?;
 			return self.insert_account(vault, extended.secret().as_raw().clone(), password);
 		}
 		Err(Error::InvalidPassword)
@@ -478,7 +478,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(&account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.secret(password)?, derivation)?;
 			return Ok(ethkey::public_to_address(extended.public().public()));
 		}
 		Err(Error::InvalidPassword)
@@ -489,7 +489,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(&account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.secret(password)?, derivation)?;
 			let secret = extended.secret().as_raw();
 			return Ok(ethkey::sign(&secret, message)?)
 		}
@@ -19,7 +19,7 @@ extern crate ethstore;
 
 mod util;
 
-use ethstore::{EthStore, SimpleSecretStore, SecretVaultRef, StoreAccountRef};
+use ethstore::{EthStore, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef};
 use ethstore::ethkey::{Random, Generator, Secret, KeyPair, verify_address};
 use ethstore::accounts_dir::RootDiskDirectory;
 use util::TransientDir;
@@ -152,3 +152,18 @@ fn test_decrypting_files_with_short_ciphertext() {
 	assert!(verify_address(&kp1.address(), &s1, &message).unwrap());
 	assert!(verify_address(&kp2.address(), &s2, &message).unwrap());
 }
+
+#[test]
+fn import_wallet_checks_address_against_secret() {
+	let path = ::std::path::Path::new(ciphertext_path()).join("30.json");
+	let json = ::std::fs::read_to_string(path).unwrap();
+	let tampered = json.replace("31e9d1e6d844bd3a536800ef8d8be6a9975db509", "d1e64e5480bfaf733ba7d48712decb8227797a4e");
+
+	let store = EthStore::open(Box::new(TransientDir::create().unwrap())).unwrap();
+	assert!(store.import_wallet(SecretVaultRef::Root, tampered.as_bytes(), &"foo".into(), false).is_err());
+	assert!(store.import_wallet(SecretVaultRef::Root, json.as_bytes(), &"bar".into(), false).is_err());
+	assert!(store.accounts().unwrap().is_empty());
+
+	let account = store.import_wallet(SecretVaultRef::Root, json.as_bytes(), &"foo".into(), false).unwrap();
+	assert_eq!(account, StoreAccountRef::root("31e9d1e6d844bd3a536800ef8d8be6a9975db509".into()));
+}
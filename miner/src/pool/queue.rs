@@ -211,11 +211,20 @@ impl TransactionQueue {
 		limits: txpool::Options,
 		verification_options: verifier::Options,
 		strategy: PrioritizationStrategy,
+	) -> Self {
+		Self::with_scoring(limits, verification_options, scoring::NonceAndGasPrice::new(strategy))
+	}
+
+	/// Create new queue with given pool limits, initial verification options and scoring.
+	pub fn with_scoring(
+		limits: txpool::Options,
+		verification_options: verifier::Options,
+		scoring: scoring::NonceAndGasPrice,
 	) -> Self {
 		let max_count = limits.max_count;
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring, limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
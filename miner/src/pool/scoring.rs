@@ -34,13 +34,16 @@ use txpool::{self, scoring};
 use super::{verifier, PrioritizationStrategy, VerifiedTransaction, ScoredTransaction};
 
 /// Transaction with the same (sender, nonce) can be replaced only if
-/// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
-const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
+/// `new_gas_price >= old_gas_price + old_gas_price * bump / 1000`.
+/// The default bump is 12.5%.
+pub const DEFAULT_REPLACE_BUMP: u32 = 125;
 
-/// Calculate minimal gas price requirement.
+/// Calculate minimal gas price requirement, with `bump` in per mille of `old_gp`.
 #[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
-	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
+fn bump_gas_price(old_gp: U256, bump: u32) -> U256 {
+	let bump = U256::from(bump);
+	let increase = (old_gp / 1000).saturating_mul(bump).saturating_add(old_gp % 1000 * bump / 1000);
+	old_gp.saturating_add(increase)
 }
 
 /// Simple, gas-price based scoring for transactions.
@@ -48,9 +51,23 @@ fn bump_gas_price(old_gp: U256) -> U256 {
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice {
+	/// Strategy for prioritizing transactions.
+	pub strategy: PrioritizationStrategy,
+	/// Minimal gas price increase, in per mille of the old gas price, required to replace
+	/// a transaction with the same sender and nonce.
+	pub replace_bump: u32,
+}
 
 impl NonceAndGasPrice {
+	/// Create a scoring with the default replacement bump.
+	pub fn new(strategy: PrioritizationStrategy) -> Self {
+		NonceAndGasPrice {
+			strategy,
+			replace_bump: DEFAULT_REPLACE_BUMP,
+		}
+	}
+
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
 	///
 	/// Used by Verifier to quickly reject transactions that don't have any chance to get into the pool later on,
@@ -83,7 +100,7 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 		let old_gp = old.gas_price();
 		let new_gp = new.gas_price();
 
-		let min_required_gp = bump_gas_price(*old_gp);
+		let min_required_gp = bump_gas_price(*old_gp, self.replace_bump);
 
 		match min_required_gp.cmp(&new_gp) {
 			cmp::Ordering::Greater => scoring::Choice::RejectNew,
@@ -161,7 +178,7 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 
 		let tx1 = Tx {
 			nonce: 1,
@@ -199,10 +216,38 @@ mod tests {
 		assert_eq!(scoring.should_replace(&txs[3], &txs[1]), RejectNew);
 	}
 
+	#[test]
+	fn should_replace_same_sender_and_nonce_with_custom_bump() {
+		let scoring = NonceAndGasPrice {
+			strategy: PrioritizationStrategy::GasPriceOnly,
+			replace_bump: 50,
+		};
+
+		let keypair = Random.generate().unwrap();
+		let txs = vec![100, 104, 105].into_iter().map(|gas_price| {
+			let tx = Tx {
+				nonce: 1,
+				gas_price,
+				..Default::default()
+			};
+			tx.unsigned().sign(keypair.secret(), None).verified()
+		}).collect::<Vec<_>>();
+
+		assert_eq!(scoring.should_replace(&txs[0], &txs[1]), RejectNew);
+		assert_eq!(scoring.should_replace(&txs[0], &txs[2]), ReplaceOld);
+	}
+
+	#[test]
+	fn should_bump_gas_price_by_per_mille() {
+		assert_eq!(bump_gas_price(1_000.into(), DEFAULT_REPLACE_BUMP), 1_125.into());
+		assert_eq!(bump_gas_price(8.into(), DEFAULT_REPLACE_BUMP), 9.into());
+		assert_eq!(bump_gas_price(U256::max_value(), DEFAULT_REPLACE_BUMP), U256::max_value());
+	}
+
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let tx_regular_low_gas = {
 			let tx = Tx {
 				nonce: 1,
@@ -253,7 +298,7 @@ mod tests {
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
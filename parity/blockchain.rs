@@ -25,6 +25,7 @@ use hash::{keccak, KECCAK_NULL_RLP};
 use ethereum_types::{U256, H256, Address};
 use bytes::ToPretty;
 use rlp::PayloadInfo;
+use ethcore::BlockChainDB;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, Nonce, Balance, BlockChainClient, BlockId, BlockInfo, ImportBlock};
 use ethcore::error::{ImportErrorKind, BlockImportErrorKind};
@@ -39,6 +40,9 @@ use helpers::{to_client_config, execute_upgrades};
 use dir::Directories;
 use user_defaults::UserDefaults;
 use ethcore_private_tx;
+use ethtrie;
+use journaldb;
+use kvdb::DBTransaction;
 use db;
 
 #[derive(Debug, PartialEq)]
@@ -71,6 +75,8 @@ pub enum BlockchainCmd {
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
+	ExportStateNodes(ExportStateNodes),
+	ImportStateNodes(ImportStateNodes),
 }
 
 #[derive(Debug, PartialEq)]
@@ -138,6 +144,31 @@ pub struct ExportState {
 	pub max_balance: Option<U256>,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct ExportStateNodes {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub file_path: Option<String>,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub at: BlockId,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImportStateNodes {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub file_path: Option<String>,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+}
+
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
@@ -150,6 +181,8 @@ pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 		}
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
+		BlockchainCmd::ExportStateNodes(export_cmd) => execute_export_state_nodes(export_cmd),
+		BlockchainCmd::ImportStateNodes(import_cmd) => execute_import_state_nodes(import_cmd),
 	}
 }
 
@@ -701,6 +734,75 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	Ok(())
 }
 
+fn execute_export_state_nodes(cmd: ExportStateNodes) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.cache_config,
+		false,
+	)?;
+
+	let client = service.client();
+
+	let mut out: Box<io::Write> = match cmd.file_path {
+		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
+		None => Box::new(io::stdout()),
+	};
+
+	let (root, state_db) = client.state_at(cmd.at).ok_or("Specified block not found")?.drop();
+	ethtrie::export_state(state_db.as_hashdb(), &root, &mut out).map_err(|e| format!("Couldn't export state: {}", e))?;
+	info!("Export of state {:x} completed.", root);
+	Ok(())
+}
+
+fn execute_import_state_nodes(cmd: ImportStateNodes) -> Result<(), String> {
+	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+	let client_path = db_dirs.client_path(algorithm);
+
+	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, &cmd.compaction)?;
+	cmd.dirs.create_dirs(false, false)?;
+
+	let db = db::open_db(&client_path.to_str().expect("DB path could not be converted to string."),
+						 &cmd.cache_config,
+						 &cmd.compaction).map_err(|e| format!("Failed to open database: {:?}", e))?;
+
+	let mut instream: Box<io::Read> = match cmd.file_path {
+		Some(f) => Box::new(fs::File::open(&f).map_err(|_| format!("Cannot open given file: {}", f))?),
+		None => Box::new(io::stdin()),
+	};
+
+	let root = import_state_nodes(&db, algorithm, &mut instream)?;
+	info!("Import of state {:x} completed.", root);
+	Ok(())
+}
+
+/// Import a state trie stream into the state column of `db`, writing each verified batch of nodes
+/// straight to disk.
+fn import_state_nodes(db: &Arc<BlockChainDB>, algorithm: journaldb::Algorithm, input: &mut io::Read) -> Result<H256, String> {
+	let mut journal = journaldb::new(db.key_value().clone(), algorithm, ::ethcore::db::COL_STATE);
+	ethtrie::import_state_batched(input, ethtrie::IMPORT_BATCH, &mut |batch| {
+		for (key, value) in batch.nodes {
+			journaldb::emplace_precomputed(&mut *journal, key, value)?;
+		}
+		for (key, value) in batch.preimages {
+			journal.emplace(key, value);
+		}
+		let mut transaction = DBTransaction::new();
+		journal.inject(&mut transaction)?;
+		db.key_value().write(transaction)
+	}).map_err(|e| format!("Couldn't import state: {}", e))
+}
+
 pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 	let genesis_hash = spec.genesis_header().hash();
@@ -718,7 +820,13 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 
 #[cfg(test)]
 mod test {
-	use super::DataFormat;
+	use ethereum_types::H256;
+	use ethtrie::{self, FatDBMut};
+	use ethtrie::trie::TrieMut;
+	use journaldb::{self, Algorithm};
+	use tempdir::TempDir;
+	use db;
+	use super::{DataFormat, import_state_nodes};
 
 	#[test]
 	fn test_data_format_parsing() {
@@ -726,4 +834,38 @@ mod test {
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
 	}
+
+	#[test]
+	fn state_nodes_round_trip() {
+		let open = |dir: &TempDir| db::open_db(dir.path().to_str().unwrap(), &Default::default(), &Default::default()).unwrap();
+		let source_dir = TempDir::new("").unwrap();
+		let source = open(&source_dir);
+
+		let mut root = H256::default();
+		let mut journal = journaldb::new(source.key_value().clone(), Algorithm::Archive, ::ethcore::db::COL_STATE);
+		{
+			let mut trie = FatDBMut::new(journal.as_hashdb_mut(), &mut root);
+			for i in 0..500u32 {
+				trie.insert(&[i as u8, (i >> 8) as u8], &vec![i as u8; 1 + i as usize % 40]).unwrap();
+			}
+		}
+		let mut stream = Vec::new();
+		ethtrie::export_state(journal.as_hashdb(), &root, &mut stream).unwrap();
+
+		let dest_dir = TempDir::new("").unwrap();
+		let dest = open(&dest_dir);
+		assert_eq!(import_state_nodes(&dest, Algorithm::OverlayRecent, &mut &stream[..]).unwrap(), root);
+
+		// everything was written to disk, so a fresh journal exports the same stream.
+		let imported = journaldb::new(dest.key_value().clone(), Algorithm::OverlayRecent, ::ethcore::db::COL_STATE);
+		let mut exported = Vec::new();
+		ethtrie::export_state(imported.as_hashdb(), &root, &mut exported).unwrap();
+		assert_eq!(exported, stream);
+
+		let mut corrupt = stream.clone();
+		let middle = corrupt.len() / 2;
+		corrupt[middle] ^= 0x01;
+		let corrupt_dir = TempDir::new("").unwrap();
+		assert!(import_state_nodes(&open(&corrupt_dir), Algorithm::OverlayRecent, &mut &corrupt[..]).is_err());
+	}
 }
@@ -217,6 +217,28 @@ usage! {
 			CMD cmd_db_kill {
 				"Clean the database of the given --chain (default: mainnet)",
 			}
+
+			CMD cmd_db_export_state
+			{
+				"Export the nodes of the state trie of the given --chain (default: mainnet) as a verifiable stream, to be read by `db import-state`",
+
+				ARG arg_db_export_state_at: (String) = "latest",
+				"--at=[BLOCK]",
+				"Export the state at the given block, which may be an index, hash, or latest. Note that exporting the state of non-recent blocks will only work with --pruning archive",
+
+				ARG arg_db_export_state_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the exported file",
+			}
+
+			CMD cmd_db_import_state
+			{
+				"Import a state trie exported by `db export-state` into the database of the given --chain (default: mainnet)",
+
+				ARG arg_db_import_state_file: (Option<String>) = None,
+				"[FILE]",
+				"Path to the file to import from",
+			}
 		}
 
 		CMD cmd_export_hardcoded_sync
@@ -499,6 +521,10 @@ usage! {
 			"--jsonrpc-max-payload=[MB]",
 			"Specify maximum size for HTTP JSON-RPC requests in megabytes.",
 
+			ARG arg_jsonrpc_max_batch_size: (Option<usize>) = None, or |c: &Config| c.rpc.as_ref()?.max_batch_size,
+			"--jsonrpc-max-batch-size=[NUM]",
+			"Specify maximum number of requests in a single JSON-RPC batch, for all transports. Unlimited by default.",
+
 		["API and Console Options – WebSockets"]
 			FLAG flag_no_ws: (bool) = false, or |c: &Config| c.websockets.as_ref()?.disable.clone(),
 			"--no-ws",
@@ -728,6 +754,10 @@ usage! {
 			"--tx-queue-strategy=[S]",
 			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
 
+			ARG arg_tx_queue_replace_bump: (u32) = 125u32, or |c: &Config| c.mining.as_ref()?.tx_queue_replace_bump.clone(),
+			"--tx-queue-replace-bump=[PERMILLE]",
+			"Minimal gas price increase, in per mille, required to replace a queued transaction with the same sender and nonce.",
+
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
 			"--stratum-interface=[IP]",
 			"Interface address for Stratum server.",
@@ -802,6 +832,10 @@ usage! {
 			"--log-file=[FILENAME]",
 			"Specify a filename into which logging should be appended.",
 
+			ARG arg_bad_block_reports: (Option<String>) = None, or |c: &Config| c.misc.as_ref()?.bad_block_reports.clone(),
+			"--bad-block-reports=[PATH]",
+			"Specify a directory into which a report of the state changes, receipts and gas used is written for every block whose execution doesn't match its header.",
+
 		["Footprint Options"]
 			FLAG flag_scale_verifiers: (bool) = false, or |c: &Config| c.footprint.as_ref()?.scale_verifiers.clone(),
 			"--scale-verifiers",
@@ -1201,6 +1235,7 @@ struct Rpc {
 	server_threads: Option<usize>,
 	processing_threads: Option<usize>,
 	max_payload: Option<usize>,
+	max_batch_size: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1304,6 +1339,7 @@ struct Mining {
 	tx_queue_per_sender: Option<usize>,
 	tx_queue_mem_limit: Option<u32>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_replace_bump: Option<u32>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
 	tx_queue_no_unfamiliar_locals: Option<bool>,
@@ -1352,6 +1388,7 @@ struct Snapshots {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	bad_block_reports: Option<String>,
 	color: Option<bool>,
 	ports_shift: Option<u16>,
 	unsafe_expose: Option<bool>,
@@ -1450,6 +1487,11 @@ mod tests {
 		let args = Args::parse(&["parity", "snapshot", "file.dump"]).unwrap();
 		assert_eq!(args.arg_snapshot_at, "latest");
 		assert_eq!(args.arg_export_state_at, "latest");
+
+		let args = Args::parse(&["parity", "db", "export-state", "--at", "123", "state.dump"]).unwrap();
+		assert_eq!(args.arg_db_export_state_at, "123");
+		assert_eq!(args.arg_export_state_at, "latest");
+		assert_eq!(args.arg_db_export_state_file, Some("state.dump".into()));
 	}
 
 	#[test]
@@ -1556,6 +1598,8 @@ mod tests {
 			cmd_tools_hash: false,
 			cmd_db: false,
 			cmd_db_kill: false,
+			cmd_db_export_state: false,
+			cmd_db_import_state: false,
 			cmd_export_hardcoded_sync: false,
 
 			// Arguments
@@ -1568,6 +1612,8 @@ mod tests {
 			arg_export_state_format: None,
 			arg_snapshot_file: None,
 			arg_restore_file: None,
+			arg_db_export_state_file: None,
+			arg_db_import_state_file: None,
 			arg_tools_hash_file: None,
 
 			arg_signer_sign_id: None,
@@ -1657,6 +1703,7 @@ mod tests {
 			arg_jsonrpc_server_threads: None,
 			arg_jsonrpc_threads: 4,
 			arg_jsonrpc_max_payload: None,
+			arg_jsonrpc_max_batch_size: None,
 
 			// WS
 			flag_no_ws: false,
@@ -1730,6 +1777,7 @@ mod tests {
 			arg_tx_queue_per_sender: None,
 			arg_tx_queue_mem_limit: 4u32,
 			arg_tx_queue_strategy: "gas_factor".into(),
+			arg_tx_queue_replace_bump: 125u32,
 			arg_tx_queue_ban_count: Some(1u16),
 			arg_tx_queue_ban_time: Some(180u16),
 			flag_remove_solved: false,
@@ -1769,6 +1817,7 @@ mod tests {
 
 			// -- Snapshot Optons
 			arg_export_state_at: "latest".into(),
+			arg_db_export_state_at: "latest".into(),
 			arg_snapshot_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
 
@@ -1821,6 +1870,7 @@ mod tests {
 			flag_version: false,
 			arg_logging: Some("own_tx=trace".into()),
 			arg_log_file: Some("/var/log/parity.log".into()),
+			arg_bad_block_reports: None,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -1925,6 +1975,7 @@ mod tests {
 				server_threads: None,
 				processing_threads: None,
 				max_payload: None,
+				max_batch_size: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
@@ -1991,6 +2042,7 @@ mod tests {
 				tx_queue_per_sender: None,
 				tx_queue_mem_limit: None,
 				tx_queue_strategy: None,
+				tx_queue_replace_bump: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
 				tx_queue_no_unfamiliar_locals: None,
@@ -2025,6 +2077,7 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				bad_block_reports: None,
 				color: Some(true),
 				ports_shift: Some(0),
 				unsafe_expose: Some(false),
@@ -46,7 +46,7 @@ use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
 use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ExportStateNodes, ImportStateNodes, DataFormat};
 use export_hardcoded_sync::ExportHsyncCmd;
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
@@ -178,6 +178,29 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_export_state {
+			Cmd::Blockchain(BlockchainCmd::ExportStateNodes(ExportStateNodes {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				file_path: self.args.arg_db_export_state_file.clone(),
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				fat_db: fat_db,
+				tracing: tracing,
+				at: to_block_id(&self.args.arg_db_export_state_at)?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_import_state {
+			Cmd::Blockchain(BlockchainCmd::ImportStateNodes(ImportStateNodes {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				file_path: self.args.arg_db_import_state_file.clone(),
+				pruning: pruning,
+				compaction: compaction,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
@@ -383,6 +406,7 @@ impl Configuration {
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
 				whisper: whisper_config,
 				no_hardcoded_sync: self.args.flag_no_hardcoded_sync,
+				bad_block_reports: self.args.arg_bad_block_reports.as_ref().map(|path| replace_home(&self.directories().base, path)),
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -537,6 +561,7 @@ impl Configuration {
 
 			tx_queue_penalization: to_queue_penalization(self.args.arg_tx_time_limit)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.arg_tx_queue_strategy)?,
+			tx_queue_replace_bump: self.args.arg_tx_queue_replace_bump,
 			tx_queue_no_unfamiliar_locals: self.args.flag_tx_queue_no_unfamiliar_locals,
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
 
@@ -714,6 +739,7 @@ impl Configuration {
 		net_path.push("network");
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes()?;
+		ret.reserved_nodes_file = self.args.arg_reserved_peers.as_ref().map(|path| replace_home(&self.directories().base, path));
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
 		ret.client_version = {
 			let mut client_version = version();
@@ -842,6 +868,7 @@ impl Configuration {
 				Some(max) if max > 0 => max as usize,
 				_ => 5usize,
 			},
+			max_batch_size: self.args.arg_jsonrpc_max_batch_size,
 		};
 
 		Ok(conf)
@@ -1142,7 +1169,7 @@ mod tests {
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 
 	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState, ExportStateNodes, ImportStateNodes};
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
 	use helpers::{default_network_config};
@@ -1293,6 +1320,36 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_state_nodes_export_and_import() {
+		let args = vec!["parity", "db", "export-state", "--at", "100", "state.dump"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ExportStateNodes(ExportStateNodes {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("state.dump".into()),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			fat_db: Default::default(),
+			tracing: Default::default(),
+			at: BlockId::Number(100),
+		})));
+
+		let args = vec!["parity", "db", "import-state", "state.dump"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ImportStateNodes(ImportStateNodes {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("state.dump".into()),
+			pruning: Default::default(),
+			compaction: Default::default(),
+		})));
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1408,6 +1465,7 @@ mod tests {
 			no_hardcoded_sync: false,
 			no_persistent_txqueue: false,
 			whisper: Default::default(),
+			bad_block_reports: None,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1422,11 +1480,14 @@ mod tests {
 		// when
 		let conf0 = parse(&["parity"]);
 		let conf2 = parse(&["parity", "--tx-queue-strategy", "gas_price"]);
+		let conf3 = parse(&["parity", "--tx-queue-replace-bump", "100"]);
 
 		// then
 		assert_eq!(conf0.miner_options().unwrap(), mining_options);
 		mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly;
 		assert_eq!(conf2.miner_options().unwrap(), mining_options);
+		mining_options.tx_queue_replace_bump = 100;
+		assert_eq!(conf3.miner_options().unwrap(), mining_options);
 	}
 
 	#[test]
@@ -22,7 +22,7 @@ use std::sync::Arc;
 use std::path::Path;
 use blooms_db;
 use ethcore::{BlockChainDBHandler, BlockChainDB};
-use ethcore::db::NUM_COLUMNS;
+use ethcore::db::{NUM_COLUMNS, IoStats, StatsDB, CompressedStateDB};
 use ethcore::client::{ClientConfig, DatabaseCompactionProfile};
 use kvdb::KeyValueDB;
 use self::kvdb_rocksdb::{Database, DatabaseConfig};
@@ -37,6 +37,7 @@ pub use self::migration::migrate;
 
 struct AppDB {
 	key_value: Arc<KeyValueDB>,
+	stats: Arc<StatsDB<CompressedStateDB<Database>>>,
 	blooms: blooms_db::Database,
 	trace_blooms: blooms_db::Database,
 }
@@ -53,6 +54,10 @@ impl BlockChainDB for AppDB {
 	fn trace_blooms(&self) -> &blooms_db::Database {
 		&self.trace_blooms
 	}
+
+	fn io_stats(&self) -> IoStats {
+		self.stats.stats()
+	}
 }
 
 /// Open a secret store DB using the given secret store data path. The DB path is one level beneath the data path.
@@ -106,8 +111,11 @@ pub fn open_database(client_path: &str, config: &DatabaseConfig) -> io::Result<A
 	fs::create_dir_all(&blooms_path)?;
 	fs::create_dir_all(&trace_blooms_path)?;
 
+	let state_db = CompressedStateDB::open(Database::open(&config, client_path)?)?;
+	let stats = Arc::new(StatsDB::new(state_db));
 	let db = AppDB {
-		key_value: Arc::new(Database::open(&config, client_path)?),
+		key_value: stats.clone(),
+		stats,
 		blooms: blooms_db::Database::open(blooms_path)?,
 		trace_blooms: blooms_db::Database::open(trace_blooms_path)?,
 	};
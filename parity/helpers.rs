@@ -198,6 +198,7 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		max_pending_peers: 64,
 		ip_filter: IpFilter::default(),
 		reserved_nodes: Vec::new(),
+		reserved_nodes_file: None,
 		allow_non_reserved: true,
 		client_version: ::parity_version::version(),
 	}
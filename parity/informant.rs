@@ -27,6 +27,7 @@ use ethcore::client::{
 	BlockId, BlockChainClient, ChainInfo, BlockInfo, BlockChainInfo,
 	BlockQueueInfo, ChainNotify, ChainRoute, ClientReport, Client, ClientIoMessage
 };
+use ethcore::db::COMMIT_LATENCY_BUCKETS;
 use ethcore::header::BlockNumber;
 use ethcore::snapshot::{RestorationStatus, SnapshotService as SS};
 use ethcore::snapshot::service::Service as SnapshotService;
@@ -363,6 +364,17 @@ impl<T: InformantData> Informant<T> {
 				_ => String::new(),
 			},
 		);
+
+		let io = &client_report.io_stats;
+		debug!(target: "import", "Database: {} reads ({}), {} writes ({}) in {} transactions, commit latency {:?} (ms buckets {:?})",
+			io.reads,
+			format_bytes(io.bytes_read),
+			io.writes,
+			format_bytes(io.bytes_written),
+			io.transactions,
+			io.commit_latency,
+			COMMIT_LATENCY_BUCKETS,
+		);
 	}
 }
 
@@ -70,6 +70,7 @@ extern crate rpc_cli;
 extern crate node_filter;
 extern crate keccak_hash as hash;
 extern crate journaldb;
+extern crate patricia_trie_ethereum as ethtrie;
 extern crate registrar;
 
 #[macro_use]
@@ -45,6 +45,7 @@ pub struct HttpConfiguration {
 	pub server_threads: usize,
 	pub processing_threads: usize,
 	pub max_payload: usize,
+	pub max_batch_size: Option<usize>,
 }
 
 impl Default for HttpConfiguration {
@@ -59,6 +60,7 @@ impl Default for HttpConfiguration {
 			server_threads: 1,
 			processing_threads: 4,
 			max_payload: 5,
+			max_batch_size: None,
 		}
 	}
 }
@@ -137,6 +139,7 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
 	pub remote: TokioRemote,
 	pub stats: Arc<RpcStats>,
 	pub pool: Option<CpuPool>,
+	pub max_batch_size: Option<usize>,
 }
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
@@ -155,7 +158,7 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let handler = {
 		let mut handler = MetaIoHandler::with_middleware((
 			rpc::WsDispatcher::new(full_handler),
-			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone())
+			Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone(), deps.max_batch_size)
 		));
 		let apis = conf.apis.list_apis();
 		deps.apis.extend_with_set(&mut handler, &apis);
@@ -294,7 +297,7 @@ pub fn setup_apis<D>(apis: ApiSet, deps: &Dependencies<D>) -> MetaIoHandler<Meta
 	where D: rpc_apis::Dependencies
 {
 	let mut handler = MetaIoHandler::with_middleware(
-		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone())
+		Middleware::new(deps.stats.clone(), deps.apis.activity_notifier(), deps.pool.clone(), deps.max_batch_size)
 	);
 	let apis = apis.list_apis();
 	deps.apis.extend_with_set(&mut handler, &apis);
@@ -128,6 +128,7 @@ pub struct RunCmd {
 	pub no_persistent_txqueue: bool,
 	pub whisper: ::whisper::Config,
 	pub no_hardcoded_sync: bool,
+	pub bad_block_reports: Option<String>,
 }
 
 // node info fetcher for the local store.
@@ -317,6 +318,7 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 		} else {
 			None
 		},
+		max_batch_size: cmd.http_conf.max_batch_size,
 	};
 
 	// start rpc servers
@@ -531,6 +533,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
+	client_config.bad_block_reports = cmd.bad_block_reports.map(Into::into);
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -725,7 +728,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		} else {
 			None
 		},
-
+		max_batch_size: cmd.http_conf.max_batch_size,
 	};
 
 	// start rpc servers
@@ -152,6 +152,14 @@ pub fn state_pruned() -> Error {
 	}
 }
 
+pub fn fat_db_required() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request requires the preimages of state keys, which are only recorded when running with --fat-db=on.".into(),
+		data: None,
+	}
+}
+
 pub fn state_corrupt() -> Error {
 	internal("State corrupt", "")
 }
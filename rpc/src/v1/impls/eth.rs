@@ -20,13 +20,14 @@ use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
 
+use hash::keccak;
 use rlp::{self, Rlp};
 use ethereum_types::{U256, H64, H256, Address};
 use parking_lot::Mutex;
 
 use ethash::{self, SeedHashCompute};
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo};
+use ethcore::client::{BlockChainClient, BlockId, TransactionId, UncleId, StateOrBlock, StateClient, StateInfo, Call, EngineInfo, ProvingBlockChainClient};
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::header::{BlockNumber as EthBlockNumber};
 use ethcore::log_entry::LogEntry;
@@ -47,7 +48,7 @@ use v1::helpers::block_import::is_major_importing;
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256, block_number_to_id,
 };
 use v1::metadata::Metadata;
@@ -56,7 +57,7 @@ const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), e
 
 /// Eth RPC options
 pub struct EthClientOptions {
-	/// Return nonce from transaction queue when pending block not available.
+	/// Always return nonce from transaction queue, even if pending block is available.
 	pub pending_nonce_from_queue: bool,
 	/// Returns receipt from pending blocks
 	pub allow_pending_receipt_query: bool,
@@ -453,7 +454,7 @@ fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockC
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<C, SN, S, M, EM> where
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
+	C: miner::BlockChainClient + BlockChainClient + ProvingBlockChainClient + StateClient<State=T> + Call<State=T> + EngineInfo + 'static,
 	SN: SnapshotService + 'static,
 	S: SyncProvider + 'static,
 	M: MinerService<State=T> + 'static,
@@ -561,6 +562,46 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::done(res))
 	}
 
+	fn proof(&self, address: RpcH160, keys: Vec<RpcU256>, num: Trailing<BlockNumber>) -> BoxFuture<EthAccount> {
+		let id = match num.unwrap_or_default() {
+			BlockNumber::Pending => return Box::new(future::err(errors::unsupported("Proofs are not available for the pending block.", None))),
+			num => {
+				try_bf!(check_known(&*self.client, num.clone()));
+				block_number_to_id(num)
+			}
+		};
+
+		let account_address: Address = address.clone().into();
+		let account_key = keccak(account_address);
+		let (account_proof, account) = match self.client.prove_account(account_key, id) {
+			Some(proof) => proof,
+			None => return Box::new(future::err(errors::state_pruned())),
+		};
+
+		let mut storage_proof = Vec::with_capacity(keys.len());
+		for key in keys {
+			let position: U256 = RpcU256::into(key);
+			match self.client.prove_storage(account_key, keccak(H256::from(position)), id) {
+				Some((proof, value)) => storage_proof.push(StorageProof {
+					key,
+					value: U256::from(value).into(),
+					proof: proof.into_iter().map(Bytes::new).collect(),
+				}),
+				None => return Box::new(future::err(errors::state_pruned())),
+			}
+		}
+
+		Box::new(future::ok(EthAccount {
+			address,
+			account_proof: account_proof.into_iter().map(Bytes::new).collect(),
+			balance: account.balance.into(),
+			code_hash: account.code_hash.into(),
+			nonce: account.nonce.into(),
+			storage_hash: account.storage_root.into(),
+			storage_proof,
+		}))
+	}
+
 	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256> {
 		let address: Address = RpcH160::into(address);
 
@@ -573,15 +614,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				let nonce = self.miner
 					.pending_state(info.best_block_number)
 					.and_then(|s| s.nonce(&address).ok())
-					.or_else(|| {
-						warn!("Fallback to `BlockId::Latest`");
-						self.client.nonce(&address, BlockId::Latest)
-					});
+					// Not sealing: the queue knows about executable transactions on top of the latest nonce.
+					.unwrap_or_else(|| self.miner.next_nonce(&*self.client, &address));
 
-				match nonce {
-					Some(nonce) => Ok(nonce.into()),
-					None => Err(errors::database("latest nonce missing"))
-				}
+				Ok(nonce.into())
 			},
 			number => {
 				try_bf!(check_known(&*self.client, number.clone()));
@@ -31,7 +31,7 @@ use ethcore::ids::BlockId;
 use light::client::LightChainClient;
 
 use jsonrpc_core::{Result, BoxFuture};
-use jsonrpc_core::futures::Future;
+use jsonrpc_core::futures::{future, Future};
 use jsonrpc_macros::Trailing;
 use v1::helpers::{self, errors, ipfs, SigningQueue, SignerService, NetworkSettings};
 use v1::helpers::dispatch::LightDispatcher;
@@ -44,7 +44,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader,
+	AccountInfo, HwAccountInfo, Header, RichHeader, StateDiff, AccountOverride,
 };
 use Host;
 
@@ -216,10 +216,13 @@ impl Parity for ParityClient {
 		Ok(Bytes::new(version_data()))
 	}
 
-	fn gas_price_histogram(&self) -> BoxFuture<Histogram> {
+	fn gas_price_histogram(&self, blocks: Trailing<u64>) -> BoxFuture<Histogram> {
+		if blocks.is_some() {
+			return Box::new(future::err(errors::light_unimplemented(Some("Light clients sample a fixed number of blocks.".into()))));
+		}
+
 		Box::new(self.light_dispatch.gas_price_corpus()
-			.and_then(|corpus| corpus.histogram(10).ok_or_else(errors::not_enough_data))
-			.map(Into::into))
+			.and_then(|corpus| Histogram::from_corpus(&corpus).ok_or_else(errors::not_enough_data)))
 	}
 
 	fn unsigned_transactions_count(&self) -> Result<usize> {
@@ -245,6 +248,10 @@ impl Parity for ParityClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn state_diff(&self, _: H256) -> Result<StateDiff> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -422,4 +429,8 @@ impl Parity for ParityClient {
 	fn call(&self, _requests: Vec<CallRequest>, _block: Trailing<BlockNumber>) -> Result<Vec<Bytes>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn call_with_overrides(&self, _request: CallRequest, _overrides: BTreeMap<H160, AccountOverride>, _block: Trailing<BlockNumber>) -> Result<Bytes> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -31,9 +31,10 @@ use ethcore::client::{BlockChainClient, StateClient, Call};
 use ethcore::ids::BlockId;
 use ethcore::miner::{self, MinerService};
 use ethcore::state::StateInfo;
+use ethcore::state_override::StateOverride;
 use ethcore_logger::RotatingLogger;
 use updater::{Service as UpdateService};
-use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::{BoxFuture, Error, Result};
 use jsonrpc_core::futures::future;
 use jsonrpc_macros::Trailing;
 use v1::helpers::{self, errors, fake_sign, ipfs, SigningQueue, SignerService, NetworkSettings};
@@ -45,7 +46,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader,
+	AccountInfo, HwAccountInfo, RichHeader, StateDiff, AccountOverride,
 	block_number_to_id
 };
 use Host;
@@ -95,6 +96,14 @@ impl<C, M, U> ParityClient<C, M, U> where
 	}
 }
 
+/// Error for a fat DB query on the state at `id` which came back empty.
+fn listing_unavailable<C: StateClient>(client: &C, id: BlockId) -> Error {
+	match client.state_at(id) {
+		Some(_) => errors::fat_db_required(),
+		None => errors::state_pruned(),
+	}
+}
+
 impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	S: StateInfo + 'static,
 	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
@@ -225,13 +234,14 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		Ok(Bytes::new(version_data()))
 	}
 
-	fn gas_price_histogram(&self) -> BoxFuture<Histogram> {
-		Box::new(future::done(self.client
-			.gas_price_corpus(100)
-			.histogram(10)
-			.ok_or_else(errors::not_enough_data)
-			.map(Into::into)
-		))
+	fn gas_price_histogram(&self, blocks: Trailing<u64>) -> BoxFuture<Histogram> {
+		let blocks = blocks.unwrap_or(100);
+		if blocks == 0 {
+			return Box::new(future::err(errors::invalid_params("blocks", "at least one block has to be sampled")));
+		}
+
+		let corpus = self.client.gas_price_corpus(blocks as usize);
+		Box::new(future::done(Histogram::from_corpus(&corpus).ok_or_else(errors::not_enough_data)))
 	}
 
 	fn unsigned_transactions_count(&self) -> Result<usize> {
@@ -259,9 +269,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			num => block_number_to_id(num)
 		};
 
-		Ok(self.client
-			.list_accounts(number, after.map(Into::into).as_ref(), count)
-			.map(|a| a.into_iter().map(Into::into).collect()))
+		match self.client.list_accounts(number, after.map(Into::into).as_ref(), count) {
+			Some(accounts) => Ok(Some(accounts.into_iter().map(Into::into).collect())),
+			None => Err(listing_unavailable(&*self.client, number)),
+		}
 	}
 
 	fn list_storage_keys(&self, address: H160, count: u64, after: Option<H256>, block_number: Trailing<BlockNumber>) -> Result<Option<Vec<H256>>> {
@@ -274,9 +285,27 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			num => block_number_to_id(num)
 		};
 
-		Ok(self.client
-			.list_storage(number, &address.into(), after.map(Into::into).as_ref(), count)
-			.map(|a| a.into_iter().map(Into::into).collect()))
+		let address = address.into();
+		match self.client.list_storage(number, &address, after.map(Into::into).as_ref(), count) {
+			Some(keys) => Ok(Some(keys.into_iter().map(Into::into).collect())),
+			// missing accounts have nothing to list
+			None if self.client.state_at(number).is_some() && self.client.storage_root(&address, number).is_none() => Ok(None),
+			None => Err(listing_unavailable(&*self.client, number)),
+		}
+	}
+
+	fn state_diff(&self, hash: H256) -> Result<StateDiff> {
+		let id = BlockId::Hash(hash.into());
+		let header = self.client.block_header(id).ok_or_else(errors::unknown_block)?;
+
+		match self.client.state_diff(id) {
+			Some(diff) => Ok(diff.into()),
+			// the parent's state is pruned first
+			None => Err(listing_unavailable(&*self.client, match header.number() {
+				0 => id,
+				_ => BlockId::Hash(header.parent_hash()),
+			})),
+		}
 	}
 
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
@@ -449,4 +478,33 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 				.map(|res| res.into_iter().map(|res| res.output.into()).collect())
 				.map_err(errors::call)
 	}
+
+	fn call_with_overrides(&self, request: CallRequest, overrides: BTreeMap<H160, AccountOverride>, num: Trailing<BlockNumber>) -> Result<Bytes> {
+		let signed = fake_sign::sign_call(request.into())?;
+		let overrides: StateOverride = overrides.into_iter().map(|(address, account)| (address.into(), account.into())).collect();
+
+		let num = num.unwrap_or_default();
+
+		let (mut state, header) = if num == BlockNumber::Pending {
+			let info = self.client.chain_info();
+			let state = self.miner.pending_state(info.best_block_number).ok_or(errors::state_pruned())?;
+			let header = self.miner.pending_block_header(info.best_block_number).ok_or(errors::state_pruned())?;
+
+			(state, header)
+		} else {
+			let id = block_number_to_id(num);
+			let state = self.client.state_at(id).ok_or(errors::state_pruned())?;
+			let header = self.client.block_header(id).ok_or(errors::state_pruned())?.decode().map_err(errors::decode)?;
+
+			(state, header)
+		};
+
+		self.client.override_state(&mut state, &overrides).map_err(errors::call)?;
+
+		let executed = self.client.call(&signed, Default::default(), &mut state, &header).map_err(errors::call)?;
+		match executed.exception {
+			Some(ref exception) => Err(errors::vm(exception, &executed.output)),
+			None => Ok(executed.output.into()),
+		}
+	}
 }
@@ -187,15 +187,36 @@ pub struct Middleware<T: ActivityNotifier = ClientNotifier> {
 	stats: Arc<RpcStats>,
 	notifier: T,
 	pool: Option<CpuPool>,
+	max_batch_size: Option<usize>,
 }
 
 impl<T: ActivityNotifier> Middleware<T> {
 	/// Create new Middleware with stats counter and activity notifier.
-	pub fn new(stats: Arc<RpcStats>, notifier: T, pool: Option<CpuPool>) -> Self {
+	/// Batches with more than `max_batch_size` requests are refused.
+	pub fn new(stats: Arc<RpcStats>, notifier: T, pool: Option<CpuPool>, max_batch_size: Option<usize>) -> Self {
 		Middleware {
 			stats,
 			notifier,
 			pool,
+			max_batch_size,
+		}
+	}
+
+	/// Error for an empty batch or a batch over the size limit.
+	fn check_batch(&self, request: &rpc::Request) -> Option<rpc::Error> {
+		let calls = match *request {
+			rpc::Request::Batch(ref calls) => calls.len(),
+			rpc::Request::Single(_) => return None,
+		};
+
+		match self.max_batch_size {
+			_ if calls == 0 => Some(rpc::Error::invalid_request()),
+			Some(max) if calls > max => Some(rpc::Error {
+				code: rpc::ErrorCode::InvalidRequest,
+				message: format!("Batch of {} requests exceeds the limit of {}.", calls, max),
+				data: None,
+			}),
+			_ => None,
 		}
 	}
 
@@ -221,6 +242,10 @@ impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T>
 		self.notifier.active();
 		self.stats.count_request();
 
+		if let Some(error) = self.check_batch(&request) {
+			return B(Box::new(rpc::futures::future::ok(Some(rpc::Response::from(error, Some(rpc::Version::V2))))));
+		}
+
 		let id = match request {
 			rpc::Request::Single(rpc::Call::MethodCall(ref call)) => Some(call.id.clone()),
 			_ => None,
@@ -257,7 +282,56 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
-	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use std::sync::Arc;
+	use jsonrpc_core as rpc;
+	use super::{RateCalculator, StatsCalculator, RpcStats, ActivityNotifier, Middleware};
+
+	struct NoopNotifier;
+
+	impl ActivityNotifier for NoopNotifier {
+		fn active(&self) {}
+	}
+
+	fn batch_handler(max_batch_size: Option<usize>) -> rpc::MetaIoHandler<(), Middleware<NoopNotifier>> {
+		let mut io = rpc::MetaIoHandler::with_middleware(
+			Middleware::new(Arc::new(RpcStats::default()), NoopNotifier, None, max_batch_size)
+		);
+		io.add_method("hello", |_| Ok(rpc::Value::String("world".into())));
+		io
+	}
+
+	#[test]
+	fn should_answer_each_request_in_a_batch() {
+		let io = batch_handler(Some(4));
+		let request = r#"[
+			{"jsonrpc": "2.0", "method": "hello", "params": [], "id": 1},
+			{"jsonrpc": "2.0", "method": "unknown", "params": [], "id": 2},
+			{"jsonrpc": "2.0", "method": "hello", "params": []},
+			{"foo": "bar"}
+		]"#;
+		let response = r#"[{"jsonrpc":"2.0","result":"world","id":1},{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":2},{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}]"#;
+
+		assert_eq!(io.handle_request_sync(request, ()), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_refuse_empty_and_oversized_batches() {
+		let io = batch_handler(Some(1));
+		let call = r#"{"jsonrpc": "2.0", "method": "hello", "params": [], "id": 1}"#;
+
+		assert_eq!(
+			io.handle_request_sync("[]", ()),
+			Some(r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Invalid request"},"id":null}"#.to_owned())
+		);
+		assert_eq!(
+			io.handle_request_sync(&format!("[{}, {}]", call, call), ()),
+			Some(r#"{"jsonrpc":"2.0","error":{"code":-32600,"message":"Batch of 2 requests exceeds the limit of 1."},"id":null}"#.to_owned())
+		);
+		assert_eq!(
+			io.handle_request_sync(&format!("[{}]", call), ()),
+			Some(r#"[{"jsonrpc":"2.0","result":"world","id":1}]"#.to_owned())
+		);
+	}
 
 	#[test]
 	fn should_calculate_rate() {
@@ -178,6 +178,36 @@ fn eth_get_balance() {
 	assert_eq!(tester.handler.handle_request_sync(req_new_acc).unwrap(), res_new_acc);
 }
 
+#[test]
+fn eth_get_proof() {
+	use serde_json::{self, Value};
+
+	let chain = extract_chain!("BlockchainTests/bcWalletTest/wallet2outOf3txs");
+	let tester = EthTester::from_chain(&chain);
+
+	let proof = |address: &str| -> Value {
+		let request = format!(r#"{{
+			"jsonrpc": "2.0",
+			"method": "eth_getProof",
+			"params": ["{}", ["0x0"], "latest"],
+			"id": 1
+		}}"#, address);
+		let response: Value = serde_json::from_str(&tester.handler.handle_request_sync(&request).unwrap()).unwrap();
+		response["result"].clone()
+	};
+
+	let account = proof("0xaaaf5374fce5edbc8e2a8697c15331677e6ebaaa");
+	assert_eq!(account["balance"], "0x9");
+	assert!(!account["accountProof"].as_array().unwrap().is_empty());
+	assert_eq!(account["storageProof"][0]["key"], "0x0");
+
+	// the proof of a missing account shows where its path ends
+	let missing = proof("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+	assert_eq!(missing["balance"], "0x0");
+	assert!(!missing["accountProof"].as_array().unwrap().is_empty());
+	assert_eq!(missing["storageProof"][0]["value"], "0x0");
+}
+
 #[test]
 fn eth_block_number() {
 	let chain = extract_chain!("BlockchainTests/bcGasPricerTest/RPC_API_Test");
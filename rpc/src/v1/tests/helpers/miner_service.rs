@@ -137,18 +137,13 @@ impl MinerService for TestMinerService {
 	}
 
 	/// Imports transactions to transaction queue.
-	fn import_external_transactions<C: Nonce + Sync>(&self, chain: &C, transactions: Vec<UnverifiedTransaction>)
+	fn import_external_transactions<C: Nonce + Sync>(&self, _chain: &C, transactions: Vec<UnverifiedTransaction>)
 		-> Vec<Result<(), transaction::Error>>
 	{
 		// lets assume that all txs are valid
 		let transactions: Vec<_> = transactions.into_iter().map(|tx| SignedTransaction::new(tx).unwrap()).collect();
 		self.imported_transactions.lock().extend_from_slice(&transactions);
 
-		for sender in transactions.iter().map(|tx| tx.sender()) {
-			let nonce = self.next_nonce(chain, &sender);
-			self.next_nonces.write().insert(sender, nonce);
-		}
-
 		transactions
 			.iter()
 			.map(|_| Ok(()))
@@ -163,14 +158,9 @@ impl MinerService for TestMinerService {
 	}
 
 	/// Imports transactions to queue - treats as local based on trusted flag, config, and tx source
-	fn import_claimed_local_transaction<C: Nonce + Sync>(&self, chain: &C, pending: PendingTransaction, _trusted: bool)
+	fn import_claimed_local_transaction<C: Nonce + Sync>(&self, _chain: &C, pending: PendingTransaction, _trusted: bool)
 		-> Result<(), transaction::Error> {
 
-		// keep the pending nonces up to date
-		let sender = pending.transaction.sender();
-		let nonce = self.next_nonce(chain, &sender);
-		self.next_nonces.write().insert(sender, nonce);
-
 		// lets assume that all txs are valid
 		self.imported_transactions.lock().push(pending.transaction);
 
@@ -251,7 +241,14 @@ impl MinerService for TestMinerService {
 	}
 
 	fn next_nonce<C: Nonce + Sync>(&self, _chain: &C, address: &Address) -> U256 {
-		self.next_nonces.read().get(address).cloned().unwrap_or_default()
+		// imported transactions fill the nonces they use
+		let queued = self.imported_transactions.lock().iter()
+			.filter(|tx| tx.sender() == *address)
+			.map(|tx| tx.nonce + 1)
+			.max()
+			.unwrap_or_default();
+		let next = self.next_nonces.read().get(address).cloned().unwrap_or_default();
+		::std::cmp::max(queued, next)
 	}
 
 	fn is_currently_sealing(&self) -> bool {
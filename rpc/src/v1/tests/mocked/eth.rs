@@ -474,6 +474,19 @@ fn rpc_eth_storage_at() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_proof_pending() {
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getProof",
+		"params": ["0x0000000000000000000000000000000000000001", [], "pending"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Proofs are not available for the pending block."},"id":1}"#;
+
+	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_transaction_count() {
 	let request = r#"{
@@ -513,6 +526,47 @@ fn rpc_eth_transaction_count_next_nonce() {
 	assert_eq!(tester.io.handle_request_sync(request2), Some(response2.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_count_pending_without_pending_block() {
+	let tester = EthTester::default();
+	let address = tester.accounts_provider.new_account(&"abcd".into()).unwrap();
+	tester.accounts_provider.unlock_account_permanently(address, "abcd".into()).unwrap();
+
+	let count_request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionCount",
+		"params": [""#.to_owned() + &format!("0x{:x}", address) + r#"", "pending"],
+		"id": 1
+	}"#;
+	let count_response = |count: &str| Some(r#"{"jsonrpc":"2.0","result":""#.to_owned() + count + r#"","id":1}"#);
+	assert_eq!(tester.io.handle_request_sync(&count_request), count_response("0x0"));
+
+	for nonce in 0..2u64 {
+		let t = Transaction {
+			nonce: U256::from(nonce),
+			gas_price: U256::from(0x9184e72a000u64),
+			gas: U256::from(0x76c0),
+			action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+			value: U256::from(0x9184e72au64),
+			data: vec![]
+		};
+		let signature = tester.accounts_provider.sign(address, None, t.hash(None)).unwrap();
+		let t = t.with_signature(signature, None);
+
+		let send_request = r#"{
+			"jsonrpc": "2.0",
+			"method": "eth_sendRawTransaction",
+			"params": ["0x"#.to_owned() + &rlp::encode(&t).into_vec().to_hex() + r#""],
+			"id": 1
+		}"#;
+		let send_response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + &format!("0x{:x}", t.hash()) + r#"","id":1}"#;
+		assert_eq!(tester.io.handle_request_sync(&send_request), Some(send_response));
+	}
+
+	assert!(tester.miner.pending_state(0).is_none());
+	assert_eq!(tester.io.handle_request_sync(&count_request), count_response("0x2"));
+}
+
 #[test]
 fn rpc_eth_block_transaction_count_by_hash() {
 	let request = r#"{
@@ -27,7 +27,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader,
+	AccountInfo, HwAccountInfo, RichHeader, StateDiff, AccountOverride,
 };
 
 build_rpc_trait! {
@@ -103,9 +103,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_defaultExtraData")]
 		fn default_extra_data(&self) -> Result<Bytes>;
 
-		/// Returns distribution of gas price in latest blocks.
+		/// Returns distribution of gas price in latest blocks, along with its median and 90th percentile.
+		/// Optionally takes the number of blocks to sample (100 by default, full nodes only).
 		#[rpc(name = "parity_gasPriceHistogram")]
-		fn gas_price_histogram(&self) -> BoxFuture<Histogram>;
+		fn gas_price_histogram(&self, Trailing<u64>) -> BoxFuture<Histogram>;
 
 		/// Returns number of unsigned transactions waiting in the signer queue (if signer enabled)
 		/// Returns error when signer is disabled
@@ -124,15 +125,20 @@ build_rpc_trait! {
 		#[rpc(name = "parity_registryAddress")]
 		fn registry_address(&self) -> Result<Option<H160>>;
 
-		/// Returns all addresses if Fat DB is enabled (`--fat-db`), or null if not.
+		/// Returns all addresses if Fat DB is enabled (`--fat-db`), or an error if not.
 		#[rpc(name = "parity_listAccounts")]
 		fn list_accounts(&self, u64, Option<H160>, Trailing<BlockNumber>) -> Result<Option<Vec<H160>>>;
 
 		/// Returns all storage keys of the given address (first parameter) if Fat DB is enabled (`--fat-db`),
-		/// or null if not.
+		/// or an error if not. Returns null if the account doesn't exist.
 		#[rpc(name = "parity_listStorageKeys")]
 		fn list_storage_keys(&self, H160, u64, Option<H256>, Trailing<BlockNumber>) -> Result<Option<Vec<H256>>>;
 
+		/// Returns the changes the block with the given hash made to the state, found by comparing its
+		/// state with its parent's rather than by executing the block. Requires Fat DB (`--fat-db`).
+		#[rpc(name = "parity_stateDiff")]
+		fn state_diff(&self, H256) -> Result<StateDiff>;
+
 		/// Encrypt some data with a public key under ECIES.
 		/// First parameter is the 512-byte destination public key, second is the message.
 		#[rpc(name = "parity_encryptMessage")]
@@ -218,5 +224,11 @@ build_rpc_trait! {
 		/// Call contract, returning the output data.
 		#[rpc(name = "parity_call")]
 		fn call(&self, Vec<CallRequest>, Trailing<BlockNumber>) -> Result<Vec<Bytes>>;
+
+		/// Call contract on a state with the given accounts changed first, returning the output data.
+		/// The accounts are mapped to their new balance, nonce, code and storage slots, and created
+		/// if they don't exist. The changes only last for the call.
+		#[rpc(name = "parity_callWithOverrides")]
+		fn call_with_overrides(&self, CallRequest, BTreeMap<H160, AccountOverride>, Trailing<BlockNumber>) -> Result<Bytes>;
 	}
 }
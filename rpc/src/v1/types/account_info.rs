@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use v1::types::{Bytes, H160, H256, U256};
+
 /// Account information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct AccountInfo {
@@ -41,3 +43,34 @@ pub struct HwAccountInfo {
 	/// Device manufacturer.
 	pub manufacturer: String,
 }
+
+/// Account state with merkle proofs against the block's state root (used by `eth_getProof`).
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all="camelCase")]
+pub struct EthAccount {
+	/// Account address
+	pub address: H160,
+	/// Account trie nodes from the state root to the account, proving its absence if missing
+	pub account_proof: Vec<Bytes>,
+	/// Account balance
+	pub balance: U256,
+	/// Hash of the account code
+	pub code_hash: H256,
+	/// Account nonce
+	pub nonce: U256,
+	/// Root of the account storage trie
+	pub storage_hash: H256,
+	/// Proofs of the requested storage slots
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// Storage slot value with merkle proof against the account's storage root.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StorageProof {
+	/// Storage key
+	pub key: U256,
+	/// Storage value
+	pub value: U256,
+	/// Storage trie nodes from the storage root to the slot
+	pub proof: Vec<Bytes>,
+}
@@ -19,9 +19,61 @@ use std::str::FromStr;
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
 use serde;
-use rustc_hex::{ToHex, FromHex};
+use rustc_hex::{ToHex, FromHex, FromHexError};
 use ethereum_types::{H64 as Eth64, H160 as Eth160, H256 as Eth256, H520 as Eth520, H512 as Eth512, Bloom as Eth2048};
 
+/// Error parsing a hash with `from_hex_str`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashParseError {
+	/// No hex digits were given.
+	Empty,
+	/// Odd number of hex digits.
+	OddLength,
+	/// More hex digits than fit into the hash.
+	TooLong {
+		/// Maximal number of digits.
+		max: usize,
+		/// Number of digits given.
+		found: usize,
+	},
+	/// A character which isn't a hex digit, at the given position of the input.
+	InvalidCharacter(char, usize),
+}
+
+impl fmt::Display for HashParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HashParseError::Empty => write!(f, "empty hash"),
+			HashParseError::OddLength => write!(f, "odd number of hex digits"),
+			HashParseError::TooLong { max, found } => write!(f, "{} hex digits given, at most {} expected", found, max),
+			HashParseError::InvalidCharacter(c, pos) => write!(f, "invalid hex character {:?} at position {}", c, pos),
+		}
+	}
+}
+
+/// Decode `s` into the tail of `out`, leaving the leading bytes untouched.
+fn from_padded_hex(s: &str, out: &mut [u8]) -> Result<(), HashParseError> {
+	let prefix = if s.starts_with("0x") || s.starts_with("0X") { 2 } else { 0 };
+	let digits = &s[prefix..];
+	if digits.is_empty() {
+		return Err(HashParseError::Empty);
+	}
+	if digits.len() % 2 != 0 {
+		return Err(HashParseError::OddLength);
+	}
+	if digits.len() > out.len() * 2 {
+		return Err(HashParseError::TooLong { max: out.len() * 2, found: digits.len() });
+	}
+
+	let bytes = digits.from_hex().map_err(|e| match e {
+		FromHexError::InvalidHexCharacter(c, pos) => HashParseError::InvalidCharacter(c, pos + prefix),
+		FromHexError::InvalidHexLength => HashParseError::OddLength,
+	})?;
+	let offset = out.len() - bytes.len();
+	out[offset..].copy_from_slice(&bytes);
+	Ok(())
+}
+
 macro_rules! impl_hash {
 	($name: ident, $other: ident, $size: expr) => {
 		/// Hash serialization
@@ -48,6 +100,25 @@ macro_rules! impl_hash {
 			}
 		}
 
+		impl fmt::LowerHex for $name {
+			/// Always the full width, lowercase, with `0x` prefix, as used in JSON-RPC.
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				write!(f, "0x{}", self.0.to_hex())
+			}
+		}
+
+		impl $name {
+			/// Parse user-supplied hex leniently: the `0x` prefix is optional, either case is
+			/// accepted and input shorter than the hash is padded with leading zeros.
+			///
+			/// `FromStr` stays strict.
+			pub fn from_hex_str(s: &str) -> Result<Self, HashParseError> {
+				let mut result = [0u8; $size];
+				from_padded_hex(s, &mut result)?;
+				Ok($name(result))
+			}
+		}
+
 		impl<T> From<T> for $name where $other: From<T> {
 			fn from(o: T) -> Self {
 				$name($other::from(o).0)
@@ -110,9 +181,7 @@ macro_rules! impl_hash {
 		impl serde::Serialize for $name {
 			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
 			where S: serde::Serializer {
-				let mut hex = "0x".to_owned();
-				hex.push_str(&self.0.to_hex());
-				serializer.serialize_str(&hex)
+				serializer.serialize_str(&format!("{:x}", self))
 			}
 		}
 
@@ -124,7 +193,7 @@ macro_rules! impl_hash {
 					type Value = $name;
 
 					fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-						write!(formatter, "a 0x-prefixed, padded, hex-encoded hash with length {}", $size * 2)
+						write!(formatter, "a 0x-prefixed, hex-encoded hash with length up to {}", $size * 2)
 					}
 
 					fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: serde::de::Error {
@@ -132,18 +201,8 @@ macro_rules! impl_hash {
 						if value.len() < 2 || &value[0..2] != "0x" {
 							return Err(E::custom("expected a hex-encoded hash with 0x prefix"));
 						}
-						if value.len() != 2 + $size * 2 {
-							return Err(E::invalid_length(value.len() - 2, &self));
-						}
 
-						match value[2..].from_hex() {
-							Ok(ref v) => {
-								let mut result = [0u8; $size];
-								result.copy_from_slice(v);
-								Ok($name(result))
-							},
-							Err(e) => Err(E::custom(format!("invalid hex value: {:?}", e))),
-						}
+						$name::from_hex_str(value).map_err(|e| E::custom(format!("invalid hash: {}", e)))
 					}
 
 					fn visit_string<E>(self, value: String) -> Result<Self::Value, E> where E: serde::de::Error {
@@ -163,3 +222,39 @@ impl_hash!(H256, Eth256, 32);
 impl_hash!(H512, Eth512, 64);
 impl_hash!(H520, Eth520, 65);
 impl_hash!(H2048, Eth2048, 256);
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{H160, H256, HashParseError};
+
+	#[test]
+	fn should_parse_lenient_hex() {
+		let one = H256::from_hex_str("0x01").unwrap();
+		assert_eq!(one, H256::from_hex_str("01").unwrap());
+		assert_eq!(one, H256::from_hex_str(&format!("0X{:064}", 1)).unwrap());
+		assert_eq!(one.0[31], 1);
+		assert!(one.0[..31].iter().all(|b| *b == 0));
+
+		assert_eq!(H160::from_hex_str("0xAbCd").unwrap(), H160::from_hex_str("abcd").unwrap());
+		assert_eq!(format!("{:x}", H160::from_hex_str("AbCd").unwrap()), "0x000000000000000000000000000000000000abcd");
+	}
+
+	#[test]
+	fn should_reject_malformed_hex() {
+		assert_eq!(H256::from_hex_str("0x"), Err(HashParseError::Empty));
+		assert_eq!(H256::from_hex_str("0xabc"), Err(HashParseError::OddLength));
+		assert_eq!(H256::from_hex_str("0x0g"), Err(HashParseError::InvalidCharacter('g', 3)));
+		assert_eq!(H160::from_hex_str(&"00".repeat(21)), Err(HashParseError::TooLong { max: 40, found: 42 }));
+	}
+
+	#[test]
+	fn should_deserialize_short_hash() {
+		let padded: H160 = serde_json::from_str(r#""0x1234""#).unwrap();
+		assert_eq!(padded, H160::from_hex_str("1234").unwrap());
+		assert_eq!(serde_json::to_string(&padded).unwrap(), r#""0x0000000000000000000000000000000000001234""#);
+
+		assert!(serde_json::from_str::<H160>(r#""1234""#).is_err());
+		assert!(serde_json::from_str::<H160>(r#""0x123""#).is_err());
+	}
+}
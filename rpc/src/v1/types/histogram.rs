@@ -16,8 +16,16 @@
 
 //! Gas prices histogram.
 
+use std::collections::BTreeMap;
+
+use stats::Corpus;
 use v1::types::U256;
 
+/// Number of buckets in the histogram.
+const BUCKETS: usize = 10;
+/// Percentiles of the gas price reported alongside the histogram.
+const PERCENTILES: [usize; 2] = [50, 90];
+
 /// Values of RPC settings.
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -27,13 +35,45 @@ pub struct Histogram {
 	pub bucket_bounds: Vec<U256>,
 	/// Transacion counts for each bucket.
 	pub counts: Vec<usize>,
+	/// Gas prices at given percentiles of the sampled transactions.
+	pub percentiles: BTreeMap<usize, U256>,
 }
 
-impl From<::stats::Histogram<::ethereum_types::U256>> for Histogram {
-	fn from(h: ::stats::Histogram<::ethereum_types::U256>) -> Self {
-		Histogram {
-			bucket_bounds: h.bucket_bounds.into_iter().map(Into::into).collect(),
-			counts: h.counts
-		}
+impl Histogram {
+	/// Create a histogram of the given gas price corpus.
+	/// Returns `None` if there wasn't any transaction sampled.
+	pub fn from_corpus(corpus: &Corpus<::ethereum_types::U256>) -> Option<Self> {
+		let histogram = corpus.histogram(BUCKETS)?;
+		let percentiles = PERCENTILES.iter()
+			.filter_map(|p| corpus.percentile(*p).map(|gas_price| (*p, (*gas_price).into())))
+			.collect();
+
+		Some(Histogram {
+			bucket_bounds: histogram.bucket_bounds.into_iter().map(Into::into).collect(),
+			counts: histogram.counts,
+			percentiles,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use stats::Corpus;
+	use ethereum_types::U256;
+	use super::Histogram;
+
+	#[test]
+	fn histogram_serialization() {
+		let corpus: Corpus<U256> = (1u64..101).map(U256::from).collect();
+		let histogram = Histogram::from_corpus(&corpus).unwrap();
+		let serialized = serde_json::to_string(&histogram.percentiles).unwrap();
+		assert_eq!(serialized, r#"{"50":"0x32","90":"0x5a"}"#);
+	}
+
+	#[test]
+	fn empty_corpus_has_no_histogram() {
+		let corpus: Corpus<U256> = Vec::new().into();
+		assert!(Histogram::from_corpus(&corpus).is_none());
 	}
 }
@@ -0,0 +1,73 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use ethcore::state_override;
+use v1::types::{Bytes, H256, U256};
+
+/// Changes made to an account before making a call.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountOverride {
+	/// Balance
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: Option<U256>,
+	/// Code
+	pub code: Option<Bytes>,
+	/// Storage slots to set, the others keep their values
+	pub state: Option<BTreeMap<H256, H256>>,
+}
+
+impl Into<state_override::AccountOverride> for AccountOverride {
+	fn into(self) -> state_override::AccountOverride {
+		state_override::AccountOverride {
+			balance: self.balance.map(Into::into),
+			nonce: self.nonce.map(Into::into),
+			code: self.code.map(Into::into),
+			storage: self.state.unwrap_or_default().into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::AccountOverride;
+
+	#[test]
+	fn account_override_deserialization() {
+		let s = r#"{
+			"balance": "0x10",
+			"code": "0x6000",
+			"state": {
+				"0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+			}
+		}"#;
+		let account: AccountOverride = serde_json::from_str(s).unwrap();
+
+		assert_eq!(account.balance, Some(0x10u64.into()));
+		assert_eq!(account.nonce, None);
+		assert_eq!(account.code, Some(vec![0x60, 0x00].into()));
+		assert_eq!(account.state.unwrap().into_iter().collect::<Vec<_>>(), vec![(1u64.into(), 2u64.into())]);
+	}
+
+	#[test]
+	fn account_override_rejects_unknown_fields() {
+		assert!(serde_json::from_str::<AccountOverride>(r#"{"balance": "0x10", "stateDiff": {}}"#).is_err());
+	}
+}
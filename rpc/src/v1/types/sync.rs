@@ -76,6 +76,8 @@ pub struct PeerNetworkInfo {
 	/// Local endpoint address
 	#[serde(rename="localAddress")]
 	pub local_address: String,
+	/// True if the connection was initiated by this node.
+	pub originated: bool,
 }
 
 /// Peer protocols information
@@ -168,6 +170,7 @@ impl From<SyncPeerInfo> for PeerInfo {
 			network: PeerNetworkInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
+				originated: p.originated,
 			},
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
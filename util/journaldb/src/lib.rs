@@ -40,6 +40,10 @@ extern crate kvdb_memorydb;
 use std::{fmt, str, io};
 use std::sync::Arc;
 
+use ethereum_types::H256;
+use hashdb::{HashDB, DBValue, Hasher};
+use keccak_hasher::KeccakHasher;
+
 /// Export the journaldb module.
 mod traits;
 mod archivedb;
@@ -146,6 +150,36 @@ pub fn new(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u
 	}
 }
 
+/// Insert `value` under `key` like `HashDB::emplace`, but only if `key` is the hash of `value`.
+///
+/// Meant for values whose hash was computed elsewhere, where a wrong hash would otherwise go
+/// unnoticed until the value is looked up. Keys which are not the hash of their value, like
+/// `AccountDB`-mangled keys or `FatDB` preimage keys, must keep using `emplace`.
+pub fn emplace_checked<DB: ?Sized + HashDB<KeccakHasher>>(db: &mut DB, key: H256, value: DBValue) -> io::Result<()> {
+	let hash = KeccakHasher::hash(&value);
+	if hash != key {
+		return Err(error_hash_mismatch(&key, &hash));
+	}
+	db.emplace(key, value);
+	Ok(())
+}
+
+/// Whether `emplace_precomputed` checks hashes, which debug builds and the `checked-emplace`
+/// feature enable.
+pub const CHECK_PRECOMPUTED: bool = cfg!(any(debug_assertions, feature = "checked-emplace"));
+
+/// Insert `value` under its precomputed hash `key`, through `emplace_checked` if
+/// `CHECK_PRECOMPUTED` is set and through `HashDB::emplace` otherwise.
+pub fn emplace_precomputed<DB: ?Sized + HashDB<KeccakHasher>>(db: &mut DB, key: H256, value: DBValue) -> io::Result<()> {
+	match CHECK_PRECOMPUTED {
+		true => emplace_checked(db, key, value),
+		false => {
+			db.emplace(key, value);
+			Ok(())
+		},
+	}
+}
+
 // all keys must be at least 12 bytes
 const DB_PREFIX_LEN : usize = ::kvdb::PREFIX_LEN;
 const LATEST_ERA_KEY : [u8; ::kvdb::PREFIX_LEN] = [ b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0 ];
@@ -158,9 +192,43 @@ fn error_negatively_reference_hash(hash: &ethereum_types::H256) -> io::Error {
 	io::Error::new(io::ErrorKind::Other, format!("Entry {} removed from database more times than it was added.", hash))
 }
 
+fn error_hash_mismatch(key: &H256, hash: &H256) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, format!("Entry {} emplaced with a value hashing to {}.", key, hash))
+}
+
 #[cfg(test)]
 mod tests {
-	use super::Algorithm;
+	use hashdb::{HashDB, DBValue};
+	use keccak::keccak;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use overlaydb::OverlayDB;
+	use super::{Algorithm, emplace_checked, emplace_precomputed, CHECK_PRECOMPUTED};
+
+	fn check_emplace(db: &mut HashDB<KeccakHasher>) {
+		assert!(emplace_checked(db, keccak(b"foo"), DBValue::from_slice(b"bar")).is_err());
+		assert!(!db.contains(&keccak(b"foo")));
+		assert!(!db.contains(&keccak(b"bar")));
+
+		emplace_checked(db, keccak(b"foo"), DBValue::from_slice(b"foo")).unwrap();
+		assert_eq!(db.get(&keccak(b"foo")).unwrap(), DBValue::from_slice(b"foo"));
+	}
+
+	#[test]
+	fn emplace_checked_rejects_wrong_hash() {
+		check_emplace(&mut MemoryDB::<KeccakHasher>::new());
+		check_emplace(&mut OverlayDB::new_temp());
+	}
+
+	#[test]
+	fn emplace_precomputed_checks_when_enabled() {
+		let mut db = MemoryDB::<KeccakHasher>::new();
+		assert_eq!(emplace_precomputed(&mut db, keccak(b"foo"), DBValue::from_slice(b"bar")).is_err(), CHECK_PRECOMPUTED);
+		assert_eq!(db.contains(&keccak(b"foo")), !CHECK_PRECOMPUTED);
+
+		emplace_precomputed(&mut db, keccak(b"bar"), DBValue::from_slice(b"bar")).unwrap();
+		assert_eq!(db.get(&keccak(b"bar")).unwrap(), DBValue::from_slice(b"bar"));
+	}
 
 	#[test]
 	fn test_journal_algorithm_parsing() {
@@ -44,6 +44,19 @@ pub struct OverlayDB {
 	column: Option<u32>,
 }
 
+/// Summary of the changes written out by a commit.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+	/// Number of keys written for the first time.
+	pub inserted: usize,
+	/// Number of keys already in the backing database whose reference count changed.
+	pub updated: usize,
+	/// Number of keys deleted as their reference count dropped to zero.
+	pub deleted: usize,
+	/// Total size of the written entries, in bytes.
+	pub bytes: usize,
+}
+
 struct Payload {
 	count: u32,
 	value: DBValue,
@@ -90,52 +103,83 @@ impl OverlayDB {
 		Self::new(backing, None)
 	}
 
-	/// Commit all operations in a single batch.
-	#[cfg(test)]
-	pub fn commit(&mut self) -> io::Result<u32> {
+	/// Commit all operations, writing them to the backing database in a single transaction.
+	///
+	/// If the commit fails, the overlay is left untouched so that it can be retried.
+	pub fn commit(&mut self) -> io::Result<CommitSummary> {
 		let mut batch = self.backing.transaction();
-		let res = self.commit_to_batch(&mut batch)?;
-		self.backing.write(batch).map(|_| res).map_err(|e| e.into())
+		let summary = self.changes_to_batch(&mut batch)?;
+		self.backing.write(batch)?;
+		self.reset_overlay();
+		Ok(summary)
 	}
 
 	/// Commit all operations to given batch.
+	///
+	/// If the commit fails, the overlay is left untouched.
 	pub fn commit_to_batch(&mut self, batch: &mut DBTransaction) -> io::Result<u32> {
-		let mut ret = 0u32;
-		let mut deletes = 0usize;
-		for i in self.overlay.drain() {
-			let (key, (value, rc)) = i;
-			if rc != 0 {
-				match self.payload(&key) {
-					Some(x) => {
-						let total_rc: i32 = x.count as i32 + rc;
-						if total_rc < 0 {
-							return Err(error_negatively_reference_hash(&key));
-						}
-						let payload = Payload::new(total_rc as u32, x.value);
-						deletes += if self.put_payload_in_batch(batch, &key, &payload) {1} else {0};
+		let summary = self.changes_to_batch(batch)?;
+		self.reset_overlay();
+		Ok((summary.inserted + summary.updated + summary.deleted) as u32)
+	}
+
+	/// Put all operations in the overlay into the given batch, without clearing the overlay.
+	fn changes_to_batch(&self, batch: &mut DBTransaction) -> io::Result<CommitSummary> {
+		let mut summary = CommitSummary::default();
+		for (key, rc) in self.overlay.keys() {
+			if rc == 0 {
+				continue;
+			}
+
+			let (payload, existing) = match self.payload(&key) {
+				Some(x) => {
+					let total_rc: i32 = x.count as i32 + rc;
+					if total_rc < 0 {
+						return Err(error_negatively_reference_hash(&key));
+					}
+					(Payload::new(total_rc as u32, x.value), true)
+				}
+				None => {
+					if rc < 0 {
+						return Err(error_negatively_reference_hash(&key));
 					}
-					None => {
-						if rc < 0 {
-							return Err(error_negatively_reference_hash(&key));
-						}
-						let payload = Payload::new(rc as u32, value);
-						self.put_payload_in_batch(batch, &key, &payload);
+					let (value, _) = self.overlay.raw(&key).expect("key is taken from the overlay; qed");
+					(Payload::new(rc as u32, value), false)
+				}
+			};
+
+			match self.put_payload_in_batch(batch, &key, &payload) {
+				Some(bytes) => {
+					summary.bytes += bytes;
+					match existing {
+						true => summary.updated += 1,
+						false => summary.inserted += 1,
 					}
-				};
-				ret += 1;
+				},
+				None => summary.deleted += 1,
 			}
 		}
-		trace!("OverlayDB::commit() deleted {} nodes", deletes);
-		Ok(ret)
+		trace!("OverlayDB::commit() deleted {} nodes", summary.deleted);
+		Ok(summary)
 	}
 
 	/// Revert all operations on this object (i.e. `insert()`s and `remove()`s) since the
 	/// last `commit()`.
-	pub fn revert(&mut self) { self.overlay.clear(); }
+	pub fn revert(&mut self) { self.reset_overlay(); }
+
+	/// Get the number of keys with uncommitted changes.
+	pub fn pending(&self) -> usize { self.overlay.keys().len() }
 
 	/// Get the number of references that would be committed.
 	pub fn commit_refs(&self, key: &H256) -> i32 { self.overlay.raw(key).map_or(0, |(_, refs)| refs) }
 
+	/// Returns heap memory size used by the uncommitted overlay.
+	pub fn mem_used(&self) -> usize { self.overlay.mem_used() }
+
+	/// Empty the overlay. Draining rather than clearing it frees its capacity as well, so that
+	/// `mem_used` goes back to what an empty overlay uses.
+	fn reset_overlay(&mut self) { self.overlay.drain(); }
+
 	/// Get the refs and value of the given key.
 	fn payload(&self, key: &H256) -> Option<Payload> {
 		self.backing.get(self.column, key)
@@ -143,14 +187,16 @@ impl OverlayDB {
 			.map(|d| decode(&d).expect("decoding db value failed"))
 	}
 
-	/// Put the refs and value of the given key, possibly deleting it from the db.
-	fn put_payload_in_batch(&self, batch: &mut DBTransaction, key: &H256, payload: &Payload) -> bool {
+	/// Put the refs and value of the given key, possibly deleting it from the db. Returns the
+	/// number of bytes written, or `None` if the key was deleted.
+	fn put_payload_in_batch(&self, batch: &mut DBTransaction, key: &H256, payload: &Payload) -> Option<usize> {
 		if payload.count > 0 {
-			batch.put(self.column, key, &encode(payload));
-			false
+			let encoded = encode(payload);
+			batch.put(self.column, key, &encoded);
+			Some(encoded.len())
 		} else {
 			batch.delete(self.column, key);
-			true
+			None
 		}
 	}
 }
@@ -246,6 +292,47 @@ fn overlaydb_revert() {
 	assert!(!m.contains(&bar));          // bar is gone.
 }
 
+#[test]
+fn overlaydb_mem_used() {
+	let mut m = OverlayDB::new_temp();
+	let empty = m.mem_used();
+	let keys: Vec<_> = (0..100u32).map(|i| m.insert(&[i as u8; 64])).collect();
+	assert!(m.mem_used() > empty);
+	m.commit().unwrap();
+	assert_eq!(m.mem_used(), empty);
+
+	for key in &keys {
+		m.remove(key);
+	}
+	assert!(m.mem_used() > empty);
+	m.revert();
+	assert_eq!(m.mem_used(), empty);
+
+	let mut batch = m.backing.transaction();
+	m.insert(b"dog");
+	m.commit_to_batch(&mut batch).unwrap();
+	assert_eq!(m.mem_used(), empty);
+}
+
+#[test]
+fn overlaydb_pending_revert() {
+	let mut trie = OverlayDB::new_temp();
+	let h = trie.insert(b"hello world");
+	trie.commit().unwrap();
+	assert_eq!(trie.pending(), 0);
+
+	trie.remove(&h);
+	trie.insert(b"foo");
+	trie.insert(b"foo");
+	assert_eq!(trie.pending(), 2);
+	assert_eq!(trie.get(&h), None);
+
+	trie.revert();
+	assert_eq!(trie.pending(), 0);
+	assert_eq!(trie.get(&h).unwrap(), DBValue::from_slice(b"hello world"));
+	assert_eq!(trie.commit().unwrap(), Default::default());
+}
+
 #[test]
 fn overlaydb_overlay_insert_and_remove() {
 	let mut trie = OverlayDB::new_temp();
@@ -299,6 +386,31 @@ fn overlaydb_negative() {
 	trie.remove(&h);	//bad - sends us into negative refs.
 	assert_eq!(trie.get(&h), None);
 	assert!(trie.commit().is_err());
+
+	// the failed commit left the overlay in place.
+	assert_eq!(trie.commit_refs(&h), -2);
+	trie.insert(b"hello world");
+	trie.commit().unwrap();
+	assert_eq!(trie.get(&h), None);
+}
+
+#[test]
+fn overlaydb_commit_summary() {
+	let mut trie = OverlayDB::new_temp();
+	let hfoo = trie.insert(b"foo");
+	let hbar = trie.insert(b"bar");
+	let summary = trie.commit().unwrap();
+	assert_eq!((summary.inserted, summary.updated, summary.deleted), (2, 0, 0));
+	assert!(summary.bytes > 0);
+
+	trie.insert(b"foo");
+	trie.remove(&hbar);
+	trie.insert(b"baz");
+	let summary = trie.commit().unwrap();
+	assert_eq!((summary.inserted, summary.updated, summary.deleted), (1, 1, 1));
+	assert_eq!(trie.commit_refs(&hfoo), 0);
+	assert_eq!(trie.get(&hfoo).unwrap(), DBValue::from_slice(b"foo"));
+	assert_eq!(trie.get(&hbar), None);
 }
 
 #[test]
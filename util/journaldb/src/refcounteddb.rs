@@ -102,7 +102,7 @@ impl JournalDB for RefCountedDB {
 	}
 
 	fn mem_used(&self) -> usize {
-		self.inserts.heap_size_of_children() + self.removes.heap_size_of_children()
+		self.forward.mem_used() + self.inserts.heap_size_of_children() + self.removes.heap_size_of_children()
  	}
 
 	fn is_empty(&self) -> bool {
@@ -225,6 +225,22 @@ mod tests {
 		RefCountedDB::new(backing, None)
 	}
 
+	#[test]
+	fn mem_used_includes_overlay() {
+		let mut jdb = new_db();
+		let empty = jdb.mem_used();
+		for i in 0..100u32 {
+			jdb.insert(&[i as u8; 64]);
+		}
+		let journal = jdb.inserts.heap_size_of_children() + jdb.removes.heap_size_of_children();
+		let used = jdb.mem_used();
+		assert!(used > empty + journal);
+
+		jdb.commit_batch(0, &keccak(b"0"), None).unwrap();
+		assert_eq!(jdb.forward.mem_used(), empty);
+		assert!(jdb.mem_used() < used);
+	}
+
 	#[test]
 	fn long_history() {
 		// history is 3
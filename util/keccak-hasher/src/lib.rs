@@ -37,3 +37,59 @@ impl Hasher for KeccakHasher {
 		out.into()
 	}
 }
+
+/// Incremental Keccak-256, for input which isn't contiguous in memory.
+#[derive(Clone)]
+pub struct KeccakStream(Keccak);
+
+impl Default for KeccakStream {
+	fn default() -> Self {
+		KeccakStream::new()
+	}
+}
+
+impl KeccakStream {
+	/// Start hashing nothing.
+	pub fn new() -> Self {
+		KeccakStream(Keccak::new_keccak256())
+	}
+
+	/// Append `input` to the hashed data.
+	pub fn update(&mut self, input: &[u8]) {
+		self.0.update(input);
+	}
+
+	/// Hash of everything passed to `update`.
+	pub fn finalize(self) -> H256 {
+		let mut out = [0; 32];
+		self.0.finalize(&mut out);
+		out.into()
+	}
+}
+
+/// Keccak-256 of `a` followed by `b`.
+pub fn keccak_pair(a: &[u8], b: &[u8]) -> H256 {
+	let mut stream = KeccakStream::new();
+	stream.update(a);
+	stream.update(b);
+	stream.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+	use hashdb::Hasher;
+	use super::{KeccakHasher, KeccakStream, keccak_pair};
+
+	#[test]
+	fn stream_matches_one_shot() {
+		assert_eq!(KeccakStream::new().finalize(), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".parse().unwrap());
+		assert_eq!(keccak_pair(b"hello ", b"world"), "47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad".parse().unwrap());
+
+		let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+		let mut stream = KeccakStream::new();
+		for chunk in data.chunks(33) {
+			stream.update(chunk);
+		}
+		assert_eq!(stream.finalize(), KeccakHasher::hash(&data));
+	}
+}
@@ -0,0 +1,118 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reserved nodes file: one node address per line, blank lines and lines starting with `#`
+//! are ignored. Updates leave the other lines, comments included, as they are.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use node_table::{Node, NodeId};
+
+/// Read the node addresses listed in the file.
+pub fn load(path: &Path) -> io::Result<Vec<String>> {
+	Ok(entries(&fs::read_to_string(path)?).map(str::to_owned).collect())
+}
+
+/// Append `node` to the file, unless a line already names the same node.
+/// The file is created if it doesn't exist.
+pub fn add(path: &Path, node: &str) -> io::Result<()> {
+	let id = node_id(node)?;
+	let mut content = read_if_exists(path)?;
+	if entries(&content).any(|entry| names(entry, &id)) {
+		return Ok(());
+	}
+
+	if !content.is_empty() && !content.ends_with('\n') {
+		content.push('\n');
+	}
+	content.push_str(node.trim());
+	content.push('\n');
+	fs::write(path, content)
+}
+
+/// Remove every line naming `node` from the file.
+pub fn remove(path: &Path, node: &str) -> io::Result<()> {
+	let id = node_id(node)?;
+	let content = read_if_exists(path)?;
+	if !entries(&content).any(|entry| names(entry, &id)) {
+		return Ok(());
+	}
+
+	let kept: String = content.lines()
+		.filter(|line| !names(line.trim(), &id))
+		.map(|line| format!("{}\n", line))
+		.collect();
+	fs::write(path, kept)
+}
+
+fn entries<'a>(content: &'a str) -> Box<Iterator<Item = &'a str> + 'a> {
+	Box::new(content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')))
+}
+
+fn node_id(node: &str) -> io::Result<NodeId> {
+	Node::from_str(node.trim())
+		.map(|node| node.id)
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e)))
+}
+
+fn names(entry: &str, id: &NodeId) -> bool {
+	!entry.starts_with('#') && Node::from_str(entry).map_or(false, |node| &node.id == id)
+}
+
+fn read_if_exists(path: &Path) -> io::Result<String> {
+	match fs::read_to_string(path) {
+		Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+		result => result,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempdir::TempDir;
+
+	const NODE_A: &str = "enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770";
+	const NODE_B: &str = "enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.45:7770";
+
+	#[test]
+	fn updates_keep_other_lines() {
+		let dir = TempDir::new("").unwrap();
+		let path = dir.path().join("reserved");
+		fs::write(&path, format!("# bootnodes\n{}\n\n", NODE_A)).unwrap();
+
+		add(&path, NODE_B).unwrap();
+		add(&path, NODE_B).unwrap();
+		assert_eq!(load(&path).unwrap(), vec![NODE_A.to_owned(), NODE_B.to_owned()]);
+
+		remove(&path, NODE_A).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), format!("# bootnodes\n\n{}\n", NODE_B));
+	}
+
+	#[test]
+	fn add_creates_the_file() {
+		let dir = TempDir::new("").unwrap();
+		let path = dir.path().join("reserved");
+		remove(&path, NODE_A).unwrap();
+		assert!(!path.exists());
+
+		add(&path, NODE_A).unwrap();
+		assert_eq!(load(&path).unwrap(), vec![NODE_A.to_owned()]);
+		assert!(add(&path, "not a node").is_err());
+	}
+}
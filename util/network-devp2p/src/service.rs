@@ -18,9 +18,13 @@ use network::{Error, NetworkConfiguration, NetworkProtocolHandler, NonReservedPe
 use network::{NetworkContext, PeerId, ProtocolId, NetworkIoMessage};
 use host::Host;
 use io::*;
+use node_table::Node;
 use parking_lot::RwLock;
+use reserved_nodes;
 use std::net::SocketAddr;
 use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 use ansi_term::Colour;
 use network::ConnectionFilter;
@@ -122,7 +126,14 @@ impl NetworkService {
 		let mut host = self.host.write();
 		let listen_addr = self.config.listen_address;
 		if host.is_none() {
-			let h = Arc::new(Host::new(self.config.clone(), self.filter.clone())
+			let mut config = self.config.clone();
+			if let Some(ref file) = self.config.reserved_nodes_file {
+				match reserved_nodes::load(Path::new(file)) {
+					Ok(nodes) => config.reserved_nodes = nodes,
+					Err(e) => warn!(target: "network", "Error reading reserved nodes file {}: {}", file, e),
+				}
+			}
+			let h = Arc::new(Host::new(config, self.filter.clone())
 				.map_err(|err| (err, listen_addr))?);
 			self.io_service.register_handler(h.clone())
 				.map_err(|err| (err.into(), listen_addr))?;
@@ -152,24 +163,28 @@ impl NetworkService {
 		self.host.read().as_ref().map(|h| h.connected_peers()).unwrap_or_else(Vec::new)
 	}
 
-	/// Try to add a reserved peer.
+	/// Try to add a reserved peer. The peer is also saved to the reserved nodes file, if any.
 	pub fn add_reserved_peer(&self, peer: &str) -> Result<(), Error> {
-		let host = self.host.read();
-		if let Some(ref host) = *host {
-			host.add_reserved_node(peer)
-		} else {
-			Ok(())
+		match *self.host.read() {
+			Some(ref host) => host.add_reserved_node(peer)?,
+			None => { Node::from_str(peer)?; },
 		}
+		if let Some(ref file) = self.config.reserved_nodes_file {
+			reserved_nodes::add(Path::new(file), peer)?;
+		}
+		Ok(())
 	}
 
-	/// Try to remove a reserved peer.
+	/// Try to remove a reserved peer. The peer is also removed from the reserved nodes file, if any.
 	pub fn remove_reserved_peer(&self, peer: &str) -> Result<(), Error> {
-		let host = self.host.read();
-		if let Some(ref host) = *host {
-			host.remove_reserved_node(peer)
-		} else {
-			Ok(())
+		match *self.host.read() {
+			Some(ref host) => host.remove_reserved_node(peer)?,
+			None => { Node::from_str(peer)?; },
 		}
+		if let Some(ref file) = self.config.reserved_nodes_file {
+			reserved_nodes::remove(Path::new(file), peer)?;
+		}
+		Ok(())
 	}
 
 	/// Set the non-reserved peer mode.
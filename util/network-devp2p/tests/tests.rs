@@ -21,7 +21,9 @@ extern crate ethcore_logger;
 extern crate ethcore_network;
 extern crate ethcore_network_devp2p;
 extern crate ethkey;
+extern crate tempdir;
 
+use std::fs;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::thread;
@@ -32,6 +34,7 @@ use ethcore_network::*;
 use ethcore_network_devp2p::NetworkService;
 use ethkey::{Random, Generator};
 use io::TimerToken;
+use tempdir::TempDir;
 
 pub struct TestProtocol {
 	drop_session: bool,
@@ -146,3 +149,46 @@ fn net_timeout() {
 		thread::sleep(Duration::from_millis(50));
 	}
 }
+
+#[test]
+fn net_reserved_peer_survives_restart() {
+	let dir = TempDir::new("").unwrap();
+	let file = dir.path().join("reserved");
+
+	let key1 = Random.generate().unwrap();
+	let mut config1 = NetworkConfiguration::new_local();
+	config1.use_secret = Some(key1.secret().clone());
+	let mut service1 = NetworkService::new(config1, None).unwrap();
+	service1.start().unwrap();
+	let _handler1 = TestProtocol::register(&mut service1, false);
+	let url1 = service1.local_url().unwrap();
+
+	let mut config2 = NetworkConfiguration::new_local();
+	config2.reserved_nodes_file = Some(file.to_str().unwrap().to_owned());
+	let mut service2 = NetworkService::new(config2.clone(), None).unwrap();
+	service2.start().unwrap();
+	let handler2 = TestProtocol::register(&mut service2, false);
+	service2.add_reserved_peer(&url1).unwrap();
+	wait_for(|| handler2.got_packet());
+	service2.stop();
+	drop(service2);
+	assert!(fs::read_to_string(&file).unwrap().contains(&url1));
+
+	let mut service2 = NetworkService::new(config2, None).unwrap();
+	service2.start().unwrap();
+	let handler2 = TestProtocol::register(&mut service2, false);
+	wait_for(|| handler2.got_packet());
+
+	service2.remove_reserved_peer(&url1).unwrap();
+	assert!(!fs::read_to_string(&file).unwrap().contains(&url1[..136]));
+}
+
+fn wait_for<F: Fn() -> bool>(condition: F) {
+	for _ in 0..200 {
+		if condition() {
+			return;
+		}
+		thread::sleep(Duration::from_millis(50));
+	}
+	panic!("timed out");
+}
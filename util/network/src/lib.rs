@@ -197,6 +197,9 @@ pub struct NetworkConfiguration {
 	pub reserved_protocols: HashMap<ProtocolId, u32>,
 	/// List of reserved node addresses.
 	pub reserved_nodes: Vec<String>,
+	/// File listing the reserved node addresses. When set, the reserved nodes are read from it
+	/// whenever the network starts, and reserved nodes added or removed at runtime are saved to it.
+	pub reserved_nodes_file: Option<String>,
 	/// The non-reserved peer mode.
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
@@ -230,6 +233,7 @@ impl NetworkConfiguration {
 			reserved_protocols: HashMap::new(),
 			ip_filter: IpFilter::default(),
 			reserved_nodes: Vec::new(),
+			reserved_nodes_file: None,
 			non_reserved_mode: NonReservedPeerMode::Accept,
 			client_version: "Parity-network".into(),
 		}
@@ -0,0 +1,125 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reference count checking of the databases backing tries, for tests.
+
+use std::collections::HashMap;
+
+use ethereum_types::H256;
+use hashdb::HashDB;
+use keccak_hasher::KeccakHasher;
+use memorydb::MemoryDB;
+use trie::TrieError;
+
+use iterator::{Walker, Direction};
+
+/// Reference counts the nodes of the tries with the given `roots` should have: one for every
+/// place a hashed node is referenced from, across all the tries.
+pub fn expected_refs(db: &MemoryDB<KeccakHasher>, roots: &[H256]) -> HashMap<H256, i32> {
+	let mut refs = HashMap::new();
+	for root in roots {
+		for node in Walker::new(db, root, Vec::new(), Direction::Forward) {
+			let hash = match node {
+				Ok(node) => node.hash,
+				// a missing node is still expected, the nodes below it can't be counted.
+				Err(e) => match *e {
+					TrieError::IncompleteDatabase(hash) => Some(hash),
+					e => panic!("malformed trie with root {:?}: {}", root, e),
+				},
+			};
+			if let Some(hash) = hash {
+				*refs.entry(hash).or_insert(0) += 1;
+			}
+		}
+	}
+	refs
+}
+
+/// Assert that `db` holds exactly the nodes of the tries with the given `roots`, each with a
+/// reference count matching the number of places it's referenced from.
+///
+/// Catches nodes which were killed too often or not at all, which a lookup based test would miss.
+pub fn assert_clean(db: &MemoryDB<KeccakHasher>, roots: &[H256]) {
+	let expected = expected_refs(db, roots);
+	let actual = db.keys();
+
+	let mut wrong: Vec<_> = actual.iter()
+		.filter(|&(hash, rc)| expected.get(hash) != Some(rc))
+		.map(|(hash, rc)| (*hash, *rc, expected.get(hash).cloned().unwrap_or(0)))
+		.chain(expected.iter()
+			.filter(|&(hash, _)| !actual.contains_key(hash))
+			.map(|(hash, rc)| (*hash, 0, *rc)))
+		.collect();
+	wrong.sort();
+
+	assert!(wrong.is_empty(), "unexpected reference counts (hash, actual, expected): {:?}", wrong);
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::HashDB;
+	use keccak_hash::KECCAK_NULL_RLP;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use test_helpers::sample_trie;
+	use TrieDBMut;
+	use super::assert_clean;
+
+	fn populate() -> (MemoryDB<KeccakHasher>, H256) {
+		let (mut memdb, mut root) = sample_trie();
+		{
+			let mut t = TrieDBMut::from_existing(&mut memdb, &mut root).unwrap();
+			for i in 0u8..50 {
+				t.remove(&[i, i]).unwrap();
+			}
+		}
+		(memdb, root)
+	}
+
+	#[test]
+	fn trie_built_by_triedbmut_is_clean() {
+		let (memdb, root) = populate();
+		assert_clean(&memdb, &[root]);
+		assert_clean(&MemoryDB::<KeccakHasher>::new(), &[KECCAK_NULL_RLP]);
+	}
+
+	#[test]
+	#[should_panic(expected = "unexpected reference counts")]
+	fn detects_node_killed_twice() {
+		let (mut memdb, root) = populate();
+		memdb.remove(&root);
+		assert_clean(&memdb, &[root]);
+	}
+
+	#[test]
+	#[should_panic(expected = "unexpected reference counts")]
+	fn detects_node_not_killed() {
+		let (mut memdb, root) = populate();
+		let node = memdb.get(&root).unwrap();
+		memdb.insert(&node);
+		assert_clean(&memdb, &[root]);
+	}
+
+	#[test]
+	#[should_panic(expected = "unexpected reference counts")]
+	fn detects_leaked_node() {
+		let (mut memdb, root) = populate();
+		memdb.insert(b"leaked");
+		assert_clean(&memdb, &[root]);
+	}
+}
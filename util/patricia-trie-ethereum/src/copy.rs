@@ -0,0 +1,117 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Copying of a Keccak/Rlp-flavoured trie into another database.
+
+use ethereum_types::H256;
+use hashdb::{HashDB, Hasher};
+use keccak_hasher::KeccakHasher;
+use trie::Trie;
+
+use iterator::{Walker, Direction, to_bytes};
+use {TrieDB, Result};
+
+/// Copy every node reachable from the root of `trie` into `dest`, returning the root, which is
+/// the same in both databases.
+///
+/// Inline nodes come along with the nodes they are inlined into. If the source database holds
+/// `FatDB` preimages for the keys of the trie, they are copied too. The walk keeps its own stack,
+/// so the depth of the trie doesn't matter.
+pub fn copy_to<'db>(trie: &'db TrieDB<'db>, dest: &mut HashDB<KeccakHasher>) -> Result<H256> {
+	let db = trie.db();
+	for visited in Walker::new(db, trie.root(), Vec::new(), Direction::Forward) {
+		let visited = visited?;
+		if let Some(hash) = visited.hash {
+			dest.emplace(hash, visited.data);
+		}
+
+		if let Some((key, _)) = visited.value {
+			let key = to_bytes(&key);
+			if key.len() == KeccakHasher::LENGTH {
+				let aux_hash = KeccakHasher::hash(&key);
+				if let Some(preimage) = db.get(&aux_hash) {
+					dest.emplace(aux_hash, preimage);
+				}
+			}
+		}
+	}
+	Ok(*trie.root())
+}
+
+#[cfg(test)]
+mod tests {
+	use hashdb::HashDB;
+	use keccak_hash::KECCAK_NULL_RLP;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::{Trie, TrieMut};
+	use {TrieDB, TrieDBMut, FatDB, FatDBMut};
+	use super::copy_to;
+
+	#[test]
+	fn copies_only_reachable_nodes() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for i in 0..100u8 {
+				t.insert(&[i, i / 3], &vec![i; 1 + i as usize % 40]).unwrap();
+			}
+		}
+		let mut other_root = KECCAK_NULL_RLP;
+		TrieDBMut::new(&mut memdb, &mut other_root).insert(b"unrelated", &[0x42; 40]).unwrap();
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(copy_to(&t, &mut dest).unwrap(), root);
+
+		let copy = TrieDB::new(&dest, &root).unwrap();
+		let expected: Vec<_> = t.iter().unwrap().map(|item| item.unwrap()).collect();
+		let items: Vec<_> = copy.iter().unwrap().map(|item| item.unwrap()).collect();
+		assert_eq!(items, expected);
+		assert!(!dest.contains(&other_root));
+	}
+
+	#[test]
+	fn copies_preimages() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = FatDBMut::new(&mut memdb, &mut root);
+			t.insert(b"dog", b"puppy").unwrap();
+			t.insert(b"horse", b"stallion").unwrap();
+		}
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		copy_to(&TrieDB::new(&memdb, &root).unwrap(), &mut dest).unwrap();
+
+		let t = FatDB::new(&memdb, &root).unwrap();
+		let copy = FatDB::new(&dest, &root).unwrap();
+		let expected: Vec<_> = t.iter().unwrap().map(|item| item.unwrap()).collect();
+		let items: Vec<_> = copy.iter().unwrap().map(|item| item.unwrap()).collect();
+		assert_eq!(items, expected);
+	}
+
+	#[test]
+	fn copies_empty_trie() {
+		let memdb = MemoryDB::<KeccakHasher>::new();
+		let root = KECCAK_NULL_RLP;
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		assert_eq!(copy_to(&TrieDB::new(&memdb, &root).unwrap(), &mut dest).unwrap(), KECCAK_NULL_RLP);
+		assert!(dest.keys().is_empty());
+	}
+}
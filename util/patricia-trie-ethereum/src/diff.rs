@@ -0,0 +1,334 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Differences between two Keccak/Rlp-flavoured tries sharing a database.
+
+use ethereum_types::H256;
+use hashdb::{HashDB, DBValue};
+use keccak_hasher::KeccakHasher;
+use rlp::DecoderError;
+use trie::{TrieError, NodeCodec, node::Node};
+
+use iterator::{NodeRef, to_bytes, extend};
+use {RlpCodec, Result};
+
+/// Change of the value stored under a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+	/// The key was added with the given value.
+	Added(DBValue),
+	/// The key was removed; it held the given value.
+	Removed(DBValue),
+	/// The value of the key changed from the first to the second value.
+	Changed(DBValue, DBValue),
+}
+
+/// Compute the keys whose values differ between the tries with roots `old` and `new`, in key
+/// order.
+///
+/// Both tries are walked in lockstep, skipping every subtree which has the same hash on both
+/// sides, so only the parts of the tries which actually differ are read.
+pub fn diff(db: &HashDB<KeccakHasher>, old: &H256, new: &H256) -> Result<Vec<(Vec<u8>, Change)>> {
+	let root = |root: &H256| match *root == RlpCodec::HASHED_NULL_NODE {
+		true => None,
+		false => Some(Subtree::Ref(Side { node: NodeRef::Hash(*root), parent: *root })),
+	};
+
+	let mut differ = Differ { db, changes: Vec::new() };
+	differ.compare(root(old), root(new), Vec::new())?;
+	Ok(differ.changes)
+}
+
+/// Reference to a node on one side of the comparison.
+struct Side {
+	node: NodeRef,
+	/// Hash of the closest hashed node, used to report errors in inline nodes.
+	parent: H256,
+}
+
+impl Side {
+	fn child(&self, hash: Option<H256>, encoded: &[u8]) -> Option<Side> {
+		match RlpCodec::is_empty_node(encoded) {
+			true => None,
+			false => Some(Side { node: NodeRef::from_encoded(encoded), parent: hash.unwrap_or(self.parent) }),
+		}
+	}
+}
+
+/// Decoded node, the nibbles of its partial path being relative to the path it is compared at.
+enum Shape {
+	Leaf(Vec<u8>, DBValue),
+	Extension(Vec<u8>, Option<Side>),
+	Branch(Vec<Option<Side>>, Option<DBValue>),
+}
+
+impl Shape {
+	/// Nibbles every key below this node starts with.
+	fn rest(&self) -> &[u8] {
+		match *self {
+			Shape::Leaf(ref rest, _) | Shape::Extension(ref rest, _) => rest,
+			Shape::Branch(..) => &[],
+		}
+	}
+
+	/// The subtree found `n` nibbles further down the partial path, `n` not exceeding its length.
+	fn skip(self, n: usize) -> Option<Subtree> {
+		match self {
+			Shape::Leaf(rest, value) => Some(Subtree::Node(Shape::Leaf(rest[n..].to_vec(), value))),
+			Shape::Extension(rest, child) => match n == rest.len() {
+				true => child.map(Subtree::Ref),
+				false => Some(Subtree::Node(Shape::Extension(rest[n..].to_vec(), child))),
+			},
+			branch => Some(Subtree::Node(branch)),
+		}
+	}
+
+	/// View the node as a branch: the value stored right at its path, and its 16 children. A leaf
+	/// or an extension with a non-empty path is a branch with a single child.
+	fn split(shape: Option<Shape>) -> (Option<DBValue>, Vec<Option<Subtree>>) {
+		let mut children: Vec<Option<Subtree>> = (0..16).map(|_| None).collect();
+		let value = match shape {
+			None => None,
+			Some(Shape::Branch(branch_children, value)) => {
+				for (child, side) in children.iter_mut().zip(branch_children) {
+					*child = side.map(Subtree::Ref);
+				}
+				value
+			},
+			Some(Shape::Leaf(ref rest, ref value)) if rest.is_empty() => Some(value.clone()),
+			Some(shape) => {
+				let nibble = *shape.rest().first().expect("only leaves are left with an empty path; qed") as usize;
+				children[nibble] = shape.skip(1);
+				None
+			},
+		};
+		(value, children)
+	}
+}
+
+/// Subtree on one side of the comparison, either still to be fetched or already decoded.
+enum Subtree {
+	Ref(Side),
+	Node(Shape),
+}
+
+struct Differ<'a> {
+	db: &'a HashDB<KeccakHasher>,
+	changes: Vec<(Vec<u8>, Change)>,
+}
+
+impl<'a> Differ<'a> {
+	/// Fetch and decode the node referenced by `side`.
+	fn decode(&self, side: &Side) -> Result<Option<Shape>> {
+		let (hash, data) = match side.node {
+			NodeRef::Hash(ref hash) => match self.db.get(hash) {
+				Some(data) => (Some(*hash), data),
+				None => return Err(Box::new(TrieError::IncompleteDatabase(*hash))),
+			},
+			NodeRef::Inline(ref data) => (None, data.clone()),
+		};
+		let error = |e: DecoderError| Box::new(TrieError::DecoderError(hash.unwrap_or(side.parent), e));
+
+		Ok(match RlpCodec::decode(&data).map_err(&error)? {
+			Node::Empty => None,
+			Node::Leaf(partial, value) => Some(Shape::Leaf(extend(&[], &partial), DBValue::from_slice(value))),
+			Node::Extension(ref partial, _) if partial.is_empty() => {
+				return Err(error(DecoderError::Custom("Extension with an empty partial path.")));
+			},
+			Node::Extension(partial, child) => Some(Shape::Extension(extend(&[], &partial), side.child(hash, child))),
+			Node::Branch(children, value) => {
+				let children = children.iter().map(|child| side.child(hash, child)).collect();
+				Some(Shape::Branch(children, value.map(DBValue::from_slice)))
+			},
+		})
+	}
+
+	fn resolve(&self, subtree: Option<Subtree>) -> Result<Option<Shape>> {
+		match subtree {
+			None => Ok(None),
+			Some(Subtree::Ref(side)) => self.decode(&side),
+			Some(Subtree::Node(shape)) => Ok(Some(shape)),
+		}
+	}
+
+	/// Compare the subtrees found at `path` on both sides.
+	///
+	/// Nodes of different kinds are lined up by nibble path, so the comparison keeps descending
+	/// into children and the ones with the same hash on both sides are still skipped when the
+	/// shape of the trie changed, e.g. when an extension got split by a new branch.
+	fn compare(&mut self, old: Option<Subtree>, new: Option<Subtree>, mut path: Vec<u8>) -> Result<()> {
+		if let (&Some(Subtree::Ref(ref old)), &Some(Subtree::Ref(ref new))) = (&old, &new) {
+			if old.node == new.node {
+				return Ok(());
+			}
+		}
+
+		let old = self.resolve(old)?;
+		let new = self.resolve(new)?;
+
+		// nibbles of the partial paths which both sides agree on can be skipped at once.
+		let shared = match (&old, &new) {
+			(&None, &None) => return Ok(()),
+			(&Some(ref shape), &None) | (&None, &Some(ref shape)) => shape.rest().len(),
+			(&Some(ref old), &Some(ref new)) => old.rest().iter().zip(new.rest()).take_while(|&(a, b)| a == b).count(),
+		};
+
+		if shared > 0 {
+			path.extend_from_slice(&old.as_ref().or(new.as_ref()).expect("not both sides are empty; qed").rest()[..shared]);
+			return self.compare(old.and_then(|shape| shape.skip(shared)), new.and_then(|shape| shape.skip(shared)), path);
+		}
+
+		let (old_value, old_children) = Shape::split(old);
+		let (new_value, new_children) = Shape::split(new);
+		self.compare_values(&path, old_value.as_ref().map(|v| &v[..]), new_value.as_ref().map(|v| &v[..]));
+		for (i, (old, new)) in old_children.into_iter().zip(new_children).enumerate() {
+			let mut child_path = path.clone();
+			child_path.push(i as u8);
+			self.compare(old, new, child_path)?;
+		}
+		Ok(())
+	}
+
+	fn compare_values(&mut self, key: &[u8], old: Option<&[u8]>, new: Option<&[u8]>) {
+		let change = match (old, new) {
+			(None, None) => return,
+			(Some(old), Some(new)) if old == new => return,
+			(Some(old), Some(new)) => Change::Changed(DBValue::from_slice(old), DBValue::from_slice(new)),
+			(Some(old), None) => Change::Removed(DBValue::from_slice(old)),
+			(None, Some(new)) => Change::Added(DBValue::from_slice(new)),
+		};
+		self.changes.push((to_bytes(key), change));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::DBValue;
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use {assert_clean, TrieDBMut};
+	use test_helpers::FetchLog;
+	use super::{diff, Change};
+
+	fn value(v: &[u8]) -> DBValue {
+		DBValue::from_slice(v)
+	}
+
+	#[test]
+	fn diff_of_small_tries() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut old = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut old);
+			t.insert(b"do", b"verb").unwrap();
+			t.insert(b"dog", b"puppy").unwrap();
+			t.insert(b"doge", b"coin").unwrap();
+			t.insert(b"horse", b"stallion").unwrap();
+		}
+
+		// built separately, so that the nodes of the old trie are not removed.
+		let mut new = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut new);
+			t.insert(b"a", b"b").unwrap();
+			t.insert(b"do", b"verb").unwrap();
+			t.insert(b"doge", b"wow").unwrap();
+			t.insert(b"dough", b"bread").unwrap();
+			t.insert(b"horse", b"stallion").unwrap();
+		}
+
+		assert_clean(&memdb, &[old, new]);
+
+		assert_eq!(diff(&memdb, &old, &new).unwrap(), vec![
+			(b"a".to_vec(), Change::Added(value(b"b"))),
+			(b"dog".to_vec(), Change::Removed(value(b"puppy"))),
+			(b"doge".to_vec(), Change::Changed(value(b"coin"), value(b"wow"))),
+			(b"dough".to_vec(), Change::Added(value(b"bread"))),
+		]);
+		assert!(diff(&memdb, &old, &old).unwrap().is_empty());
+
+		let empty = KECCAK_NULL_RLP;
+		assert_eq!(diff(&memdb, &empty, &old).unwrap().len(), 4);
+		assert!(diff(&memdb, &old, &empty).unwrap().iter().all(|&(_, ref change)| match *change {
+			Change::Removed(_) => true,
+			_ => false,
+		}));
+	}
+
+	#[test]
+	fn diff_reads_only_divergent_paths() {
+		fn build(memdb: &mut MemoryDB<KeccakHasher>, changed: Option<&H256>) -> H256 {
+			let mut root = KECCAK_NULL_RLP;
+			{
+				let mut t = TrieDBMut::new(memdb, &mut root);
+				for i in 0u32..1000 {
+					let key = keccak(&[i as u8, (i >> 8) as u8]);
+					t.insert(&key, &[i as u8; 40]).unwrap();
+				}
+				if let Some(key) = changed {
+					t.insert(key, b"changed").unwrap();
+				}
+			}
+			root
+		}
+
+		let changed = keccak(&[7u8, 0]);
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let old = build(&mut memdb, None);
+		let new = build(&mut memdb, Some(&changed));
+
+		let log = FetchLog::new(memdb);
+		assert_eq!(diff(&log, &old, &new).unwrap(), vec![
+			(changed.to_vec(), Change::Changed(value(&[7; 40]), value(b"changed"))),
+		]);
+		assert!(log.fetched.lock().unwrap().len() <= 12);
+	}
+
+	#[test]
+	fn split_extension_shares_its_child() {
+		// all the keys start with `abcd`, so the old root is an extension which the new key splits.
+		fn build(memdb: &mut MemoryDB<KeccakHasher>, split: bool) -> H256 {
+			let mut root = KECCAK_NULL_RLP;
+			{
+				let mut t = TrieDBMut::new(memdb, &mut root);
+				for i in 0u8..100 {
+					t.insert(&[0xab, 0xcd, i], &[i; 40]).unwrap();
+				}
+				if split {
+					t.insert(&[0xab, 0x00, 0x00], b"split").unwrap();
+				}
+			}
+			root
+		}
+
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let old = build(&mut memdb, false);
+		let new = build(&mut memdb, true);
+
+		let log = FetchLog::new(memdb);
+		assert_eq!(diff(&log, &old, &new).unwrap(), vec![
+			(vec![0xab, 0x00, 0x00], Change::Added(value(b"split"))),
+		]);
+		// both roots, the new branch and the extension below it, but nothing of the shared subtree.
+		assert!(log.fetched.lock().unwrap().len() <= 4);
+		assert_eq!(diff(&log, &new, &old).unwrap(), vec![
+			(vec![0xab, 0x00, 0x00], Change::Removed(value(b"split"))),
+		]);
+	}
+}
@@ -0,0 +1,97 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Lookups on the hashed-key tries by the hash of the key.
+
+use ethereum_types::H256;
+use hashdb::{DBValue, Hasher};
+use keccak_hasher::KeccakHasher;
+use trie::Trie;
+
+use {TrieDB, SecTrieDB, FatDB, Result};
+
+/// Lookups by key hash, for when only the hash of a key is known, e.g. because it was read out of
+/// another trie.
+pub trait HashedKeyLookup {
+	/// Get the value stored under the key with the given hash.
+	fn get_by_hash(&self, hash: &H256) -> Result<Option<DBValue>>;
+
+	/// Whether a value is stored under the key with the given hash.
+	fn contains_hash(&self, hash: &H256) -> Result<bool> {
+		self.get_by_hash(hash).map(|value| value.is_some())
+	}
+}
+
+impl<'db> HashedKeyLookup for SecTrieDB<'db> {
+	fn get_by_hash(&self, hash: &H256) -> Result<Option<DBValue>> {
+		self.raw().get(hash)
+	}
+}
+
+impl<'db> HashedKeyLookup for FatDB<'db> {
+	fn get_by_hash(&self, hash: &H256) -> Result<Option<DBValue>> {
+		TrieDB::new(self.db(), self.root())?.get(hash)
+	}
+}
+
+/// Access to the key preimages kept alongside a `FatDB`.
+pub trait PreimageLookup {
+	/// Get the key whose hash is `hash`, if the database holds its preimage.
+	fn preimage(&self, hash: &H256) -> Option<DBValue>;
+}
+
+impl<'db> PreimageLookup for FatDB<'db> {
+	fn preimage(&self, hash: &H256) -> Option<DBValue> {
+		self.db().get(&KeccakHasher::hash(hash))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hashdb::DBValue;
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use {SecTrieDB, SecTrieDBMut, FatDB, FatDBMut};
+	use super::{HashedKeyLookup, PreimageLookup};
+
+	#[test]
+	fn sec_trie_lookup_by_hash() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		SecTrieDBMut::new(&mut memdb, &mut root).insert(b"dog", b"puppy").unwrap();
+
+		let t = SecTrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(t.get_by_hash(&keccak(b"dog")).unwrap(), Some(DBValue::from_slice(b"puppy")));
+		assert!(t.contains_hash(&keccak(b"dog")).unwrap());
+		assert!(!t.contains_hash(&keccak(b"cat")).unwrap());
+	}
+
+	#[test]
+	fn fat_db_lookup_by_hash_and_preimage() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		FatDBMut::new(&mut memdb, &mut root).insert(b"dog", b"puppy").unwrap();
+
+		let t = FatDB::new(&memdb, &root).unwrap();
+		assert_eq!(t.get_by_hash(&keccak(b"dog")).unwrap(), Some(DBValue::from_slice(b"puppy")));
+		assert!(!t.contains_hash(&keccak(b"cat")).unwrap());
+		assert_eq!(t.preimage(&keccak(b"dog")), Some(DBValue::from_slice(b"dog")));
+		assert_eq!(t.preimage(&keccak(b"cat")), None);
+	}
+}
@@ -0,0 +1,97 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hex-prefix encoding of nibble paths, as used for the partial keys of leaf and extension nodes
+//! (Yellow Paper, appendix C).
+
+/// Hex-prefix encode `nibbles`, flagging the path as belonging to a leaf or an extension.
+pub fn hex_prefix_encode(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+	let flag = if leaf { 0x20 } else { 0x00 };
+	let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+	let rest = match nibbles.len() % 2 {
+		1 => {
+			encoded.push(flag | 0x10 | nibbles[0]);
+			&nibbles[1..]
+		},
+		_ => {
+			encoded.push(flag);
+			nibbles
+		},
+	};
+	encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+	encoded
+}
+
+/// Decode a hex-prefix encoded path into its nibbles and whether it belongs to a leaf.
+///
+/// Returns `None` if `encoded` is empty or its first byte is not a valid prefix.
+pub fn hex_prefix_decode(encoded: &[u8]) -> Option<(Vec<u8>, bool)> {
+	let first = *encoded.first()?;
+	let (flag, low) = (first >> 4, first & 0x0f);
+	if flag > 3 || (flag & 1 == 0 && low != 0) {
+		return None;
+	}
+
+	let mut nibbles = Vec::with_capacity(encoded.len() * 2);
+	if flag & 1 == 1 {
+		nibbles.push(low);
+	}
+	for byte in &encoded[1..] {
+		nibbles.push(byte >> 4);
+		nibbles.push(byte & 0x0f);
+	}
+	Some((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{hex_prefix_encode, hex_prefix_decode};
+
+	#[test]
+	fn yellow_paper_examples() {
+		let examples: &[(&[u8], bool, &[u8])] = &[
+			(&[], false, &[0x00]),
+			(&[], true, &[0x20]),
+			(&[1, 2, 3, 4, 5], false, &[0x11, 0x23, 0x45]),
+			(&[0, 1, 2, 3, 4, 5], false, &[0x00, 0x01, 0x23, 0x45]),
+			(&[0, 0xf, 1, 0xc, 0xb, 8], true, &[0x20, 0x0f, 0x1c, 0xb8]),
+			(&[0xf, 1, 0xc, 0xb, 8], true, &[0x3f, 0x1c, 0xb8]),
+		];
+
+		for &(nibbles, leaf, encoded) in examples {
+			assert_eq!(hex_prefix_encode(nibbles, leaf), encoded.to_vec());
+			assert_eq!(hex_prefix_decode(encoded), Some((nibbles.to_vec(), leaf)));
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		for len in 0..12u8 {
+			let nibbles: Vec<u8> = (0..len).map(|i| (i * 7 + len) & 0x0f).collect();
+			for &leaf in &[false, true] {
+				assert_eq!(hex_prefix_decode(&hex_prefix_encode(&nibbles, leaf)), Some((nibbles.clone(), leaf)));
+			}
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_prefixes() {
+		assert_eq!(hex_prefix_decode(&[]), None);
+		assert_eq!(hex_prefix_decode(&[0x40]), None);
+		assert_eq!(hex_prefix_decode(&[0x01, 0x23]), None);
+		assert_eq!(hex_prefix_decode(&[0x2f]), None);
+	}
+}
@@ -0,0 +1,915 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Iterators over Keccak/Rlp-flavoured tries which walk the nodes directly, so that the walk can
+//! run in either direction and be restricted to part of the key space.
+
+use ethereum_types::H256;
+use hashdb::{HashDB, DBValue, Hasher};
+use keccak_hasher::KeccakHasher;
+use rlp::DecoderError;
+use trie::{Trie, TrieError, TrieIterator, NibbleSlice, NodeCodec, node::Node};
+
+use {RlpCodec, TrieDB, FatDB, Result};
+
+/// Order in which an iterator visits the keys of a trie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	/// Ascending key order.
+	Forward,
+	/// Descending key order.
+	Reverse,
+}
+
+/// Reference to a node: either its hash or, if it is small enough to be inlined into its parent,
+/// the node itself.
+#[derive(Clone, PartialEq)]
+pub enum NodeRef {
+	Hash(H256),
+	Inline(DBValue),
+}
+
+impl NodeRef {
+	/// Read a reference to a child node from its parent.
+	pub fn from_encoded(data: &[u8]) -> Self {
+		match RlpCodec::try_decode_hash(data) {
+			Some(hash) => NodeRef::Hash(hash),
+			None => NodeRef::Inline(DBValue::from_slice(data)),
+		}
+	}
+}
+
+/// A node waiting to be visited.
+#[derive(Clone)]
+struct Pending {
+	node: NodeRef,
+	/// Nibbles of the key leading to the node.
+	path: Vec<u8>,
+	/// Number of nodes above the node.
+	depth: usize,
+	/// Hash of the closest hashed node, used to report errors in inline nodes.
+	parent: H256,
+}
+
+/// Kind of a trie node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+	/// The empty node, only found at the root of an empty trie.
+	Empty,
+	/// Node holding a value under the remainder of a key.
+	Leaf,
+	/// Node holding a run of nibbles shared by every key below it.
+	Extension,
+	/// Node branching on the next nibble of the key, possibly holding a value.
+	Branch,
+}
+
+/// A node of a trie, as visited by a walk over the trie.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieNode {
+	/// Hash of the node, or `None` if it is inlined into its parent.
+	pub hash: Option<H256>,
+	/// Hash of the node or, if it is inlined, of the closest hashed node above it.
+	pub container: H256,
+	/// Kind of the node.
+	pub kind: NodeKind,
+	/// Number of nodes above the node.
+	pub depth: usize,
+	/// Value stored in the node, along with the nibbles of its key.
+	pub value: Option<(Vec<u8>, DBValue)>,
+	/// RLP of the node.
+	pub data: DBValue,
+}
+
+enum Step {
+	/// Fetch a node and queue its children.
+	Enter(Pending),
+	/// Yield a node whose children have all been visited.
+	Exit(TrieNode),
+}
+
+/// Depth-first walk over the nodes of a trie, in key order.
+///
+/// Walking forward, nodes are yielded before their children. Walking in reverse, nodes are yielded
+/// after their children, which makes the reverse walk an exact mirror of the forward one. Subtrees
+/// which can't hold keys starting with `prefix`, which lie entirely before `bound` in the
+/// direction of the walk, or which lie outside of `lower..upper`, are never fetched.
+pub struct Walker<'db> {
+	db: &'db HashDB<KeccakHasher>,
+	start: Option<Pending>,
+	prefix: Vec<u8>,
+	direction: Direction,
+	bound: Option<Vec<u8>>,
+	lower: Option<Vec<u8>>,
+	upper: Option<Vec<u8>>,
+	/// Whether to copy out the values stored in the nodes.
+	values: bool,
+	stack: Vec<Step>,
+}
+
+impl<'db> Walker<'db> {
+	/// Walk the trie with the given root.
+	pub fn new(db: &'db HashDB<KeccakHasher>, root: &H256, prefix: Vec<u8>, direction: Direction) -> Self {
+		let start = match *root == RlpCodec::HASHED_NULL_NODE {
+			true => None,
+			false => Some(Pending { node: NodeRef::Hash(*root), path: Vec::new(), depth: 0, parent: *root }),
+		};
+		Self::from_start(db, start, prefix, direction)
+	}
+
+	/// Walk forward over the subtree below `node`, found at `path` below the hashed node `parent`.
+	/// Depths are counted from `node`.
+	pub fn subtree(db: &'db HashDB<KeccakHasher>, node: NodeRef, path: Vec<u8>, parent: H256) -> Self {
+		let start = Pending { node, path, depth: 0, parent };
+		Self::from_start(db, Some(start), Vec::new(), Direction::Forward)
+	}
+
+	fn from_start(db: &'db HashDB<KeccakHasher>, start: Option<Pending>, prefix: Vec<u8>, direction: Direction) -> Self {
+		let mut walker = Walker {
+			db,
+			start,
+			prefix,
+			direction,
+			bound: None,
+			lower: None,
+			upper: None,
+			values: true,
+			stack: Vec::new(),
+		};
+		walker.restart();
+		walker
+	}
+
+	fn restart(&mut self) {
+		self.stack.clear();
+		if let Some(ref start) = self.start {
+			self.stack.push(Step::Enter(start.clone()));
+		}
+	}
+
+	/// Restart the walk, skipping every key before `bound` in the direction of the walk.
+	fn seek(&mut self, bound: Vec<u8>) {
+		self.bound = Some(bound);
+		self.restart();
+	}
+
+	/// Yield empty values in place of the values stored in the nodes, for walks which only need
+	/// the keys.
+	fn skip_values(&mut self) {
+		self.values = false;
+	}
+
+	fn value(&self, value: &[u8]) -> DBValue {
+		match self.values {
+			true => DBValue::from_slice(value),
+			false => DBValue::new(),
+		}
+	}
+
+	/// Restrict the walk to keys within `lower..upper`, whatever its direction.
+	fn restrict(&mut self, lower: Option<Vec<u8>>, upper: Option<Vec<u8>>) {
+		self.lower = lower;
+		self.upper = upper;
+		self.restart();
+	}
+
+	/// Whether the subtree at `path` may hold keys the walk is interested in.
+	fn covers(&self, path: &[u8]) -> bool {
+		if !path.starts_with(&self.prefix) && !self.prefix.starts_with(path) {
+			return false;
+		}
+
+		// every key below `path` is at least `path`.
+		if let Some(ref lower) = self.lower {
+			if path < &lower[..] && !lower.starts_with(path) {
+				return false;
+			}
+		}
+		if let Some(ref upper) = self.upper {
+			if path >= &upper[..] {
+				return false;
+			}
+		}
+
+		match self.bound {
+			None => true,
+			Some(ref bound) if bound.starts_with(path) => true,
+			Some(ref bound) => match self.direction {
+				Direction::Forward => path > &bound[..],
+				Direction::Reverse => path < &bound[..],
+			},
+		}
+	}
+
+	/// Whether the value stored under `key` should be yielded.
+	fn wants(&self, key: &[u8]) -> bool {
+		if !key.starts_with(&self.prefix) {
+			return false;
+		}
+		if self.lower.as_ref().map_or(false, |lower| key < &lower[..]) || self.upper.as_ref().map_or(false, |upper| key >= &upper[..]) {
+			return false;
+		}
+
+		match self.bound {
+			None => true,
+			Some(ref bound) => match self.direction {
+				Direction::Forward => key >= &bound[..],
+				Direction::Reverse => key <= &bound[..],
+			},
+		}
+	}
+
+	/// Queue the children of a node, returning the node if it can be yielded straight away.
+	fn expand(&mut self, data: DBValue, hash: Option<H256>, path: Vec<u8>, depth: usize, parent: H256) -> ::std::result::Result<Option<TrieNode>, DecoderError> {
+		let mut children = Vec::new();
+		let (kind, value) = match RlpCodec::decode(&data)? {
+			Node::Empty => (NodeKind::Empty, None),
+			Node::Leaf(partial, value) => (NodeKind::Leaf, Some((extend(&path, &partial), self.value(value)))),
+			Node::Extension(partial, child) => {
+				children.push(Pending { node: NodeRef::from_encoded(child), path: extend(&path, &partial), depth: depth + 1, parent });
+				(NodeKind::Extension, None)
+			},
+			Node::Branch(nodes, value) => {
+				for (i, child) in nodes.iter().enumerate().filter(|&(_, child)| !RlpCodec::is_empty_node(child)) {
+					let mut child_path = path.clone();
+					child_path.push(i as u8);
+					children.push(Pending { node: NodeRef::from_encoded(child), path: child_path, depth: depth + 1, parent });
+				}
+				(NodeKind::Branch, value.map(|value| (path, self.value(value))))
+			},
+		};
+
+		children.retain(|child| self.covers(&child.path));
+		let visited = TrieNode { hash, container: parent, kind, depth, value, data };
+		match self.direction {
+			Direction::Forward => {
+				self.stack.extend(children.into_iter().rev().map(Step::Enter));
+				Ok(Some(visited))
+			},
+			Direction::Reverse => {
+				self.stack.push(Step::Exit(visited));
+				self.stack.extend(children.into_iter().map(Step::Enter));
+				Ok(None)
+			},
+		}
+	}
+}
+
+impl<'db> Iterator for Walker<'db> {
+	type Item = Result<TrieNode>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let Pending { node, path, depth, parent } = match self.stack.pop()? {
+				Step::Enter(pending) => pending,
+				Step::Exit(visited) => return Some(Ok(visited)),
+			};
+
+			let (hash, data) = match node {
+				NodeRef::Hash(hash) => match self.db.get(&hash) {
+					Some(data) => (Some(hash), data),
+					None => return Some(Err(Box::new(TrieError::IncompleteDatabase(hash)))),
+				},
+				NodeRef::Inline(data) => (None, data),
+			};
+
+			let parent = hash.unwrap_or(parent);
+			match self.expand(data, hash, path, depth, parent) {
+				Ok(Some(visited)) => return Some(Ok(visited)),
+				Ok(None) => {},
+				Err(e) => return Some(Err(Box::new(TrieError::DecoderError(parent, e)))),
+			}
+		}
+	}
+}
+
+/// Iterator over every node of a trie, structural nodes included, in depth-first order.
+///
+/// Walking forward, nodes are yielded before their children, in key order. Walking in reverse
+/// yields the same nodes in the opposite order. A node which can't be fetched or decoded is
+/// reported as an error and its subtree is skipped.
+pub struct NodeIterator<'db> {
+	walker: Walker<'db>,
+}
+
+impl<'db> NodeIterator<'db> {
+	/// Create an iterator over the nodes of `trie`.
+	pub fn new(trie: &'db TrieDB<'db>, direction: Direction) -> Self {
+		NodeIterator {
+			walker: Walker::new(trie.db(), trie.root(), Vec::new(), direction),
+		}
+	}
+}
+
+impl<'db> Iterator for NodeIterator<'db> {
+	type Item = Result<TrieNode>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.walker.next()
+	}
+}
+
+/// Iterator over the items of a trie, in either direction, optionally restricted to keys with a
+/// given prefix.
+///
+/// The iterator descends straight to the part of the trie covering the prefix: nodes outside of
+/// it are never fetched from the database. Seeking positions the iterator at the first key
+/// at or past the given key in the direction of iteration, that is the first key `>=` it when
+/// iterating forward and the first key `<=` it when iterating in reverse.
+pub struct ItemIterator<'db> {
+	nodes: NodeItemIterator<'db>,
+}
+
+impl<'db> ItemIterator<'db> {
+	/// Create an iterator over all items of `trie`.
+	pub fn new(trie: &'db TrieDB<'db>, direction: Direction) -> Self {
+		Self::with_nibble_prefix(trie, &[], direction)
+	}
+
+	/// Create an iterator over the items of `trie` whose keys start with `prefix`.
+	pub fn with_prefix(trie: &'db TrieDB<'db>, prefix: &[u8], direction: Direction) -> Self {
+		Self::with_nibble_prefix(trie, &to_nibbles(prefix), direction)
+	}
+
+	/// Create an iterator over the items of `trie` whose keys start with the given nibbles.
+	/// Unlike `with_prefix`, this allows for prefixes with an odd number of nibbles.
+	pub fn with_nibble_prefix(trie: &'db TrieDB<'db>, prefix: &[u8], direction: Direction) -> Self {
+		ItemIterator {
+			nodes: NodeItemIterator::with_nibble_prefix(trie, prefix, direction),
+		}
+	}
+}
+
+impl<'db> Iterator for ItemIterator<'db> {
+	type Item = Result<(Vec<u8>, DBValue)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.nodes.next().map(|item| item.map(|item| (item.key, item.value)))
+	}
+}
+
+impl<'db> TrieIterator<KeccakHasher, RlpCodec> for ItemIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> Result<()> {
+		self.nodes.seek(key)
+	}
+}
+
+/// An item of a trie, along with the node holding it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeItem {
+	/// Key of the item.
+	pub key: Vec<u8>,
+	/// Value of the item.
+	pub value: DBValue,
+	/// Hash of the node holding the item or, if that node is inlined into its parent, of the
+	/// closest hashed node above it.
+	pub hash: H256,
+	/// Whether the node holding the item is inlined into its parent.
+	pub inline: bool,
+}
+
+/// Iterator over the items of a trie which also yields the node each item is stored in, so that
+/// nodes can be deduplicated or checked against the root without hashing them again.
+///
+/// Visits the same items in the same order as `ItemIterator`.
+pub struct NodeItemIterator<'db> {
+	walker: Walker<'db>,
+}
+
+impl<'db> NodeItemIterator<'db> {
+	/// Create an iterator over all items of `trie`.
+	pub fn new(trie: &'db TrieDB<'db>, direction: Direction) -> Self {
+		Self::with_nibble_prefix(trie, &[], direction)
+	}
+
+	/// Create an iterator over the items of `trie` whose keys start with `prefix`.
+	pub fn with_prefix(trie: &'db TrieDB<'db>, prefix: &[u8], direction: Direction) -> Self {
+		Self::with_nibble_prefix(trie, &to_nibbles(prefix), direction)
+	}
+
+	/// Create an iterator over the items of `trie` whose keys start with the given nibbles.
+	pub fn with_nibble_prefix(trie: &'db TrieDB<'db>, prefix: &[u8], direction: Direction) -> Self {
+		NodeItemIterator {
+			walker: Walker::new(trie.db(), trie.root(), prefix.to_vec(), direction),
+		}
+	}
+}
+
+impl<'db> Iterator for NodeItemIterator<'db> {
+	type Item = Result<NodeItem>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			match self.walker.next()? {
+				Ok(TrieNode { hash, container, value: Some((key, value)), .. }) => {
+					if self.walker.wants(&key) {
+						return Some(Ok(NodeItem { key: to_bytes(&key), value, hash: container, inline: hash.is_none() }));
+					}
+				},
+				Ok(_) => {},
+				Err(e) => return Some(Err(e)),
+			}
+		}
+	}
+}
+
+impl<'db> TrieIterator<KeccakHasher, RlpCodec> for NodeItemIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> Result<()> {
+		self.walker.seek(to_nibbles(key));
+		Ok(())
+	}
+}
+
+/// Part of the key space of a trie, covering the keys whose first nibble lies in a given range.
+///
+/// Ranges are created by `split_ranges`, hold no reference to a `TrieDB` and can be sent to other
+/// threads, each of which then iterates over its own range.
+#[derive(Clone)]
+pub struct TrieRange<'db> {
+	db: &'db (HashDB<KeccakHasher> + Sync),
+	root: H256,
+	/// First nibble of the keys in the range.
+	start: u8,
+	/// First nibble of the keys past the range, or 16 if the range extends to the end of the trie.
+	end: u8,
+}
+
+impl<'db> TrieRange<'db> {
+	/// Create an iterator over the items in the range, which behaves like an `ItemIterator`
+	/// restricted to the range, seeking included.
+	pub fn iter(&self, direction: Direction) -> ItemIterator<'db> {
+		let mut walker = Walker::new(self.db, &self.root, Vec::new(), direction);
+		let lower = match self.start {
+			0 => None,
+			start => Some(vec![start]),
+		};
+		let upper = match self.end {
+			16 => None,
+			end => Some(vec![end]),
+		};
+		walker.restrict(lower, upper);
+		ItemIterator {
+			nodes: NodeItemIterator { walker },
+		}
+	}
+}
+
+/// Split the key space of the trie with the given root into at most `n` (and at most 16) disjoint
+/// ranges by the first nibble of the keys, in key order.
+///
+/// Iterating over every range in turn yields the same items as iterating over the whole trie.
+pub fn split_ranges<'db>(db: &'db (HashDB<KeccakHasher> + Sync), root: &H256, n: usize) -> Vec<TrieRange<'db>> {
+	let n = n.max(1).min(16);
+	(0..n)
+		.map(|i| TrieRange { db, root: *root, start: (i * 16 / n) as u8, end: ((i + 1) * 16 / n) as u8 })
+		.collect()
+}
+
+/// Iterator over the items of a `FatDB`, yielding the original keys rather than their hashes.
+///
+/// Items are visited in the order of the hashed keys, in either direction. Seeking takes an
+/// original key and positions the iterator at its hash. An item whose key preimage is missing,
+/// e.g. because it was inserted before the database was made fat, is reported as an
+/// `IncompleteDatabase` error naming the missing preimage, and iteration carries on past it.
+pub struct FatItemIterator<'db> {
+	items: ItemIterator<'db>,
+	db: &'db HashDB<KeccakHasher>,
+}
+
+impl<'db> FatItemIterator<'db> {
+	/// Create an iterator over all items of `trie`.
+	pub fn new(trie: &'db FatDB<'db>, direction: Direction) -> Self {
+		FatItemIterator {
+			items: ItemIterator {
+				nodes: NodeItemIterator {
+					walker: Walker::new(trie.db(), trie.root(), Vec::new(), direction),
+				},
+			},
+			db: trie.db(),
+		}
+	}
+}
+
+impl<'db> Iterator for FatItemIterator<'db> {
+	type Item = Result<(Vec<u8>, DBValue)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let db = self.db;
+		self.items.next().map(|item| item.and_then(|(hash, value)| Ok((preimage(db, &hash)?, value))))
+	}
+}
+
+impl<'db> TrieIterator<KeccakHasher, RlpCodec> for FatItemIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> Result<()> {
+		self.items.seek(&KeccakHasher::hash(key))
+	}
+}
+
+/// Iterator over the keys of a trie, in either direction, optionally restricted to keys with a
+/// given prefix.
+///
+/// Walks the trie exactly like `ItemIterator` and reads the same nodes from the database, since
+/// values always live inline in the leaf or branch node holding their key. Values are decoded
+/// along with their node but not returned.
+pub struct KeyIterator<'db> {
+	nodes: NodeItemIterator<'db>,
+}
+
+impl<'db> KeyIterator<'db> {
+	/// Create an iterator over all keys of `trie`.
+	pub fn new(trie: &'db TrieDB<'db>, direction: Direction) -> Self {
+		Self::with_prefix(trie, &[], direction)
+	}
+
+	/// Create an iterator over the keys of `trie` starting with `prefix`.
+	pub fn with_prefix(trie: &'db TrieDB<'db>, prefix: &[u8], direction: Direction) -> Self {
+		Self::from_walker(Walker::new(trie.db(), trie.root(), to_nibbles(prefix), direction))
+	}
+
+	fn from_walker(mut walker: Walker<'db>) -> Self {
+		walker.skip_values();
+		KeyIterator {
+			nodes: NodeItemIterator { walker },
+		}
+	}
+}
+
+impl<'db> Iterator for KeyIterator<'db> {
+	type Item = Result<Vec<u8>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.nodes.next().map(|item| item.map(|item| item.key))
+	}
+}
+
+impl<'db> TrieIterator<KeccakHasher, RlpCodec> for KeyIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> Result<()> {
+		self.nodes.seek(key)
+	}
+}
+
+/// Iterator over the original keys of a `FatDB`, in the order of their hashes.
+///
+/// Walks the trie exactly like `FatItemIterator`, reporting missing preimages the same way, but
+/// doesn't copy the values out of the nodes.
+pub struct FatKeyIterator<'db> {
+	keys: KeyIterator<'db>,
+	db: &'db HashDB<KeccakHasher>,
+}
+
+impl<'db> FatKeyIterator<'db> {
+	/// Create an iterator over all keys of `trie`.
+	pub fn new(trie: &'db FatDB<'db>, direction: Direction) -> Self {
+		FatKeyIterator {
+			keys: KeyIterator::from_walker(Walker::new(trie.db(), trie.root(), Vec::new(), direction)),
+			db: trie.db(),
+		}
+	}
+}
+
+impl<'db> Iterator for FatKeyIterator<'db> {
+	type Item = Result<Vec<u8>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let db = self.db;
+		self.keys.next().map(|hash| hash.and_then(|hash| preimage(db, &hash)))
+	}
+}
+
+impl<'db> TrieIterator<KeccakHasher, RlpCodec> for FatKeyIterator<'db> {
+	fn seek(&mut self, key: &[u8]) -> Result<()> {
+		self.keys.seek(&KeccakHasher::hash(key))
+	}
+}
+
+/// Look up the key whose hash is `hash` among the preimages kept by a `FatDB`.
+fn preimage(db: &HashDB<KeccakHasher>, hash: &[u8]) -> Result<Vec<u8>> {
+	let aux_hash = KeccakHasher::hash(hash);
+	match db.get(&aux_hash) {
+		Some(key) => Ok(key.to_vec()),
+		None => Err(Box::new(TrieError::IncompleteDatabase(aux_hash))),
+	}
+}
+
+pub fn extend(path: &[u8], partial: &NibbleSlice) -> Vec<u8> {
+	let mut path = path.to_vec();
+	path.extend((0..partial.len()).map(|i| partial.at(i)));
+	path
+}
+
+pub fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+	extend(&[], &NibbleSlice::new(bytes))
+}
+
+pub fn to_bytes(nibbles: &[u8]) -> Vec<u8> {
+	nibbles.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).cloned().unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::DBValue;
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::{Trie, TrieMut, TrieIterator};
+	use {prove, TrieDB, TrieDBMut, FatDB, FatDBMut, SecTrieDBMut, TrieError};
+	use test_helpers::{self, FetchLog};
+	use super::{ItemIterator, NodeItemIterator, NodeIterator, NodeKind, KeyIterator, FatItemIterator, FatKeyIterator, Direction, TrieRange, split_ranges};
+
+	fn populate(keys: &[Vec<u8>]) -> (MemoryDB<KeccakHasher>, H256) {
+		test_helpers::populate(keys.iter().map(|key| (key, vec![key[0]; 40])))
+	}
+
+	/// Pseudo-random keys of varying length, some of which are prefixes of others.
+	fn random_keys() -> Vec<Vec<u8>> {
+		let mut keys: Vec<_> = (0u16..300).map(|i| {
+			let hash = keccak([i as u8, (i >> 8) as u8]);
+			hash[..1 + i as usize % 6].to_vec()
+		}).collect();
+		keys.extend(vec![b"do".to_vec(), b"dog".to_vec(), b"doge".to_vec(), b"horse".to_vec()]);
+		keys
+	}
+
+	fn collect<I: Iterator<Item=::Result<(Vec<u8>, DBValue)>>>(iter: I) -> Vec<Vec<u8>> {
+		iter.map(|item| item.unwrap().0).collect()
+	}
+
+	#[test]
+	fn forward_matches_trie_iterator() {
+		let (memdb, root) = populate(&random_keys());
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let expected: Vec<_> = t.iter().unwrap().map(|item| item.unwrap()).collect();
+		let items: Vec<_> = ItemIterator::new(&t, Direction::Forward).map(|item| item.unwrap()).collect();
+		assert_eq!(items, expected);
+	}
+
+	#[test]
+	fn reverse_mirrors_forward() {
+		let (memdb, root) = populate(&random_keys());
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let mut forward: Vec<_> = ItemIterator::new(&t, Direction::Forward).map(|item| item.unwrap()).collect();
+		let reverse: Vec<_> = ItemIterator::new(&t, Direction::Reverse).map(|item| item.unwrap()).collect();
+		assert!(forward.len() > 250);
+		forward.reverse();
+		assert_eq!(reverse, forward);
+	}
+
+	#[test]
+	fn seek_in_both_directions() {
+		let (memdb, root) = populate(&random_keys());
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let all = collect(ItemIterator::new(&t, Direction::Forward));
+
+		for key in &[b"do".to_vec(), b"dof".to_vec(), b"dogz".to_vec(), vec![0x80], vec![], vec![0xff; 7]] {
+			let mut iter = ItemIterator::new(&t, Direction::Forward);
+			iter.seek(key).unwrap();
+			let expected: Vec<_> = all.iter().filter(|k| k >= &key).cloned().collect();
+			assert_eq!(collect(iter), expected);
+
+			let mut iter = ItemIterator::new(&t, Direction::Reverse);
+			iter.seek(key).unwrap();
+			let expected: Vec<_> = all.iter().rev().filter(|k| k <= &key).cloned().collect();
+			assert_eq!(collect(iter), expected);
+		}
+	}
+
+	#[test]
+	fn node_items_carry_their_node() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for key in random_keys() {
+				let len = if key.len() % 2 == 0 { 1 } else { 40 };
+				t.insert(&key, &vec![key[0]; len]).unwrap();
+			}
+		}
+
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let items: Vec<_> = NodeItemIterator::new(&t, Direction::Forward).map(|item| item.unwrap()).collect();
+		let expected: Vec<_> = ItemIterator::new(&t, Direction::Forward).map(|item| item.unwrap()).collect();
+		assert_eq!(items.iter().map(|item| (item.key.clone(), item.value.clone())).collect::<Vec<_>>(), expected);
+		assert!(items.iter().any(|item| item.inline));
+		assert!(items.iter().any(|item| !item.inline));
+
+		// the last node of a proof is the hashed node holding the item.
+		for item in &items {
+			assert_eq!(keccak(prove(&t, &item.key).unwrap().last().unwrap()), item.hash);
+		}
+	}
+
+	#[test]
+	fn ranges_cover_the_trie() {
+		fn assert_send<T: Send>() {}
+		assert_send::<TrieRange>();
+
+		let (memdb, root) = populate(&random_keys());
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let all = collect(ItemIterator::new(&t, Direction::Forward));
+
+		for &n in &[0, 1, 3, 7, 16, 40] {
+			let ranges = split_ranges(&memdb, &root, n);
+			assert_eq!(ranges.len(), n.max(1).min(16));
+
+			let forward: Vec<_> = ranges.iter().flat_map(|range| collect(range.iter(Direction::Forward))).collect();
+			assert_eq!(forward, all);
+
+			let mut reverse: Vec<_> = ranges.iter().rev().flat_map(|range| collect(range.iter(Direction::Reverse))).collect();
+			reverse.reverse();
+			assert_eq!(reverse, all);
+		}
+
+		// seeking does not leave the range.
+		let ranges = split_ranges(&memdb, &root, 4);
+		let mut iter = ranges[1].iter(Direction::Forward);
+		iter.seek(&[]).unwrap();
+		let expected: Vec<_> = all.iter().filter(|key| key[0] >> 4 >= 4 && key[0] >> 4 < 8).cloned().collect();
+		assert_eq!(collect(iter), expected);
+	}
+
+	#[test]
+	fn node_iterator_exposes_structure() {
+		let keys = vec![b"do".to_vec(), b"dog".to_vec(), b"doge".to_vec(), b"horse".to_vec()];
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for (key, value) in keys.iter().zip(&[&b"verb"[..], b"puppy", b"coin", b"stallion"]) {
+				t.insert(key, value).unwrap();
+			}
+		}
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let nodes: Vec<_> = NodeIterator::new(&t, Direction::Forward).map(|node| node.unwrap()).collect();
+		assert_eq!(nodes[0].hash, Some(root));
+		assert_eq!(nodes[0].depth, 0);
+
+		let count = |kind| nodes.iter().filter(|node| node.kind == kind).count();
+		assert_eq!((count(NodeKind::Leaf), count(NodeKind::Extension), count(NodeKind::Branch)), (2, 3, 3));
+		assert_eq!(nodes.iter().filter(|node| node.hash.is_none()).count(), 4);
+		for node in &nodes {
+			match node.hash {
+				Some(hash) => assert_eq!(keccak(&node.data), hash),
+				None => assert!(node.data.len() < 32),
+			}
+		}
+
+		let mut reverse: Vec<_> = NodeIterator::new(&t, Direction::Reverse).map(|node| node.unwrap()).collect();
+		reverse.reverse();
+		assert_eq!(reverse, nodes);
+	}
+
+	#[test]
+	fn fat_iteration_in_both_directions() {
+		let keys = random_keys();
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = FatDBMut::new(&mut memdb, &mut root);
+			for key in &keys {
+				t.insert(key, &[key[0]; 40]).unwrap();
+			}
+		}
+
+		let t = FatDB::new(&memdb, &root).unwrap();
+		let expected = collect(t.iter().unwrap());
+		let mut forward = collect(FatItemIterator::new(&t, Direction::Forward));
+		assert_eq!(forward, expected);
+
+		forward.reverse();
+		assert_eq!(collect(FatItemIterator::new(&t, Direction::Reverse)), forward);
+
+		let mut iter = FatItemIterator::new(&t, Direction::Reverse);
+		iter.seek(b"dog").unwrap();
+		assert_eq!(iter.next().unwrap().unwrap().0, b"dog".to_vec());
+	}
+
+	#[test]
+	fn fat_iteration_reports_missing_preimages() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		FatDBMut::new(&mut memdb, &mut root).insert(b"dog", b"puppy").unwrap();
+		SecTrieDBMut::from_existing(&mut memdb, &mut root).unwrap().insert(b"cat", b"kitten").unwrap();
+
+		let t = FatDB::new(&memdb, &root).unwrap();
+		let items: Vec<_> = FatItemIterator::new(&t, Direction::Forward).collect();
+		assert_eq!(items.len(), 2);
+		assert!(items.iter().any(|item| item.as_ref().ok() == Some(&(b"dog".to_vec(), DBValue::from_slice(b"puppy")))));
+		assert!(items.iter().any(|item| match *item {
+			Err(ref e) => **e == TrieError::IncompleteDatabase(keccak(keccak(b"cat"))),
+			Ok(_) => false,
+		}));
+	}
+
+	#[test]
+	fn keys_match_items() {
+		let (memdb, root) = populate(&random_keys());
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		for &direction in &[Direction::Forward, Direction::Reverse] {
+			let keys: Vec<_> = KeyIterator::new(&t, direction).map(|key| key.unwrap()).collect();
+			assert_eq!(keys, collect(ItemIterator::new(&t, direction)));
+
+			let keys: Vec<_> = KeyIterator::with_prefix(&t, b"do", direction).map(|key| key.unwrap()).collect();
+			assert_eq!(keys, collect(ItemIterator::with_prefix(&t, b"do", direction)));
+		}
+
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = FatDBMut::new(&mut memdb, &mut root);
+			for key in &random_keys() {
+				t.insert(key, &[key[0]; 400]).unwrap();
+			}
+		}
+		let t = FatDB::new(&memdb, &root).unwrap();
+		let keys: Vec<_> = FatKeyIterator::new(&t, Direction::Forward).map(|key| key.unwrap()).collect();
+		assert_eq!(keys, collect(FatItemIterator::new(&t, Direction::Forward)));
+	}
+
+	#[test]
+	fn yields_only_matching_keys() {
+		let keys: Vec<_> = (0u8..100).map(|i| vec![i, i.wrapping_mul(31)]).collect();
+		let (memdb, root) = populate(&keys);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		for &direction in &[Direction::Forward, Direction::Reverse] {
+			let mut odd = keys.iter().filter(|k| k[0] >> 4 == 0x3).cloned().collect::<Vec<_>>();
+			if direction == Direction::Reverse {
+				odd.reverse();
+			}
+
+			assert_eq!(collect(ItemIterator::with_prefix(&t, &[0x12], direction)), vec![vec![0x12, 0x12u8.wrapping_mul(31)]]);
+			assert_eq!(collect(ItemIterator::with_prefix(&t, &[0x12, 0x00], direction)), Vec::<Vec<u8>>::new());
+			assert_eq!(collect(ItemIterator::with_prefix(&t, &[0xf0], direction)), Vec::<Vec<u8>>::new());
+			assert_eq!(collect(ItemIterator::with_nibble_prefix(&t, &[0x3], direction)), odd);
+		}
+	}
+
+	#[test]
+	fn prefix_ending_inside_extension() {
+		let keys = vec![b"abcdef1".to_vec(), b"abcdef2".to_vec(), b"abcdef3".to_vec()];
+		let (memdb, root) = populate(&keys);
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let forward = Direction::Forward;
+		assert_eq!(collect(ItemIterator::with_prefix(&t, b"abc", forward)), keys);
+		assert_eq!(collect(ItemIterator::with_nibble_prefix(&t, &[6, 1, 6], forward)), keys);
+		assert_eq!(collect(ItemIterator::with_prefix(&t, b"abd", forward)), Vec::<Vec<u8>>::new());
+		assert_eq!(collect(ItemIterator::with_prefix(&t, b"abcdef2", forward)), vec![b"abcdef2".to_vec()]);
+	}
+
+	#[test]
+	fn keys_fetch_the_same_nodes_as_items() {
+		let (memdb, root) = test_helpers::populate((0u16..300).map(|i| (vec![i as u8, (i >> 8) as u8], vec![i as u8; 500])));
+		let fetched = |keys_only: bool| {
+			let log = FetchLog::new(memdb.clone());
+			{
+				let t = TrieDB::new(&log, &root).unwrap();
+				let count = match keys_only {
+					true => KeyIterator::new(&t, Direction::Forward).count(),
+					false => ItemIterator::new(&t, Direction::Forward).count(),
+				};
+				assert_eq!(count, 300);
+			}
+			log.fetched.into_inner().unwrap()
+		};
+
+		assert_eq!(fetched(true), fetched(false));
+	}
+
+	#[test]
+	fn prefix_does_not_fetch_unrelated_nodes() {
+		let keys: Vec<_> = (0u8..100).map(|i| vec![i, i.wrapping_mul(31)]).collect();
+		let (memdb, root) = populate(&keys);
+		let log = FetchLog::new(memdb);
+
+		{
+			let t = TrieDB::new(&log, &root).unwrap();
+			assert_eq!(ItemIterator::with_nibble_prefix(&t, &[0x0], Direction::Forward).count(), 16);
+		}
+
+		let t = TrieDB::new(&log.db, &root).unwrap();
+		let fetched = log.fetched.lock().unwrap();
+		for key in &keys {
+			let leaf = keccak(prove(&t, key).unwrap().last().unwrap());
+			assert_eq!(fetched.contains(&leaf), key[0] >> 4 == 0);
+		}
+	}
+}
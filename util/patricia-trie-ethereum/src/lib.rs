@@ -22,11 +22,46 @@ extern crate parity_bytes;
 extern crate ethereum_types;
 extern crate hashdb;
 extern crate keccak_hasher;
+extern crate memorydb;
 extern crate rlp;
 
+#[cfg(test)]
+extern crate keccak_hash;
+
 mod rlp_node_codec;
+#[cfg(any(test, feature = "test-helpers"))]
+mod clean;
+mod copy;
+mod diff;
+mod hashed;
+mod hex_prefix;
+mod iterator;
+mod proof;
+mod recorder;
+mod remove;
+mod stats;
+mod stream;
+#[cfg(test)]
+mod test_helpers;
+mod validate;
 
 pub use rlp_node_codec::RlpNodeCodec;
+#[cfg(any(test, feature = "test-helpers"))]
+pub use clean::{assert_clean, expected_refs};
+pub use copy::copy_to;
+pub use diff::{diff, Change};
+pub use hashed::{HashedKeyLookup, PreimageLookup};
+pub use hex_prefix::{hex_prefix_encode, hex_prefix_decode};
+pub use iterator::{
+	Direction, NodeKind, TrieNode, NodeIterator, ItemIterator, NodeItem, NodeItemIterator, KeyIterator,
+	FatItemIterator, FatKeyIterator, TrieRange, split_ranges,
+};
+pub use proof::{prove, verify_proof, ProofError};
+pub use recorder::WitnessRecorder;
+pub use remove::remove_prefix;
+pub use stats::{statistics, count_slow, TrieStatistics};
+pub use stream::{export_state, import_state, import_state_batched, ImportBatch, StreamError, IMPORT_BATCH};
+pub use validate::{validate, validate_with_progress};
 
 use ethereum_types::H256;
 use keccak_hasher::KeccakHasher;
@@ -125,3 +160,33 @@ pub type TrieFactory = trie::TrieFactory<KeccakHasher, RlpCodec>;
 pub type TrieError = trie::TrieError<H256, DecoderError>;
 /// Convenience type alias for Keccak/Rlp flavoured trie results
 pub type Result<T> = trie::Result<T, H256, DecoderError>;
+
+#[cfg(test)]
+mod tests {
+	use hashdb::{HashDB, DBValue};
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use super::FatDBMut;
+
+	#[test]
+	fn fatdb_remove_drops_preimage() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+
+		for _ in 0..16 {
+			{
+				let mut t = FatDBMut::from_existing(&mut memdb, &mut root).unwrap();
+				t.insert(b"foo", b"bar").unwrap();
+			}
+			assert_eq!(memdb.get(&keccak(keccak(b"foo"))).unwrap(), DBValue::from_slice(b"foo"));
+			{
+				let mut t = FatDBMut::from_existing(&mut memdb, &mut root).unwrap();
+				t.remove(b"foo").unwrap();
+			}
+			memdb.purge();
+			assert!(memdb.keys().is_empty());
+		}
+	}
+}
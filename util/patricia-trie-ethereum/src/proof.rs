@@ -0,0 +1,243 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Merkle proofs over Keccak/Rlp-flavoured tries.
+
+use std::collections::{HashMap, HashSet};
+use std::{error, fmt};
+
+use ethereum_types::H256;
+use hashdb::Hasher;
+use keccak_hasher::KeccakHasher;
+use parity_bytes::Bytes;
+use rlp::DecoderError;
+use trie::{Trie, Recorder, NibbleSlice, NodeCodec, node::Node};
+
+use {RlpCodec, Result};
+
+/// Error encountered while verifying a merkle proof.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ProofError {
+	/// A node on the path to the key is not part of the proof.
+	MissingNode(H256),
+	/// A node of the proof could not be decoded.
+	InvalidNode(H256, DecoderError),
+	/// The proof contains the given number of nodes which are not on the path to the key.
+	ExtraneousNodes(usize),
+}
+
+impl fmt::Display for ProofError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ProofError::MissingNode(ref hash) => write!(f, "Proof is missing node {:x}", hash),
+			ProofError::InvalidNode(ref hash, ref err) => write!(f, "Proof node {:x} is invalid: {}", hash, err),
+			ProofError::ExtraneousNodes(n) => write!(f, "Proof contains {} nodes not on the path to the key", n),
+		}
+	}
+}
+
+impl error::Error for ProofError {
+	fn description(&self) -> &str {
+		"Merkle proof verification error"
+	}
+}
+
+/// Generate a merkle proof for `key`.
+///
+/// The proof consists of the RLP of every hashed node visited while looking up the key, ordered
+/// root-first, so it can be checked knowing only the root hash. If the key is not in the trie,
+/// the nodes on the path up to the point of divergence are returned, proving its absence.
+///
+/// Secure tries (`SecTrieDB`, `FatDB`) hash the key before descending, so their proofs follow the
+/// path of `keccak(key)`.
+pub fn prove<T>(trie: &T, key: &[u8]) -> Result<Vec<Bytes>> where T: Trie<KeccakHasher, RlpCodec> {
+	let mut recorder = Recorder::new();
+	trie.get_with(key, &mut recorder)?;
+	Ok(recorder.drain().into_iter().map(|r| r.data).collect())
+}
+
+/// Check a merkle proof for `key` against `root` without building a trie.
+///
+/// Returns the value stored under `key`, or `None` if the proof demonstrates that the key is
+/// absent. Proofs are rejected if they do not reach a conclusion for the key, or if they contain
+/// nodes which are not on the path from the root to the key. Proof nodes may be given in any order.
+pub fn verify_proof(root: &H256, key: &[u8], proof: &[Bytes]) -> ::std::result::Result<Option<Bytes>, ProofError> {
+	let nodes: HashMap<H256, &[u8]> = proof.iter().map(|node| (KeccakHasher::hash(node), &node[..])).collect();
+	let mut used = HashSet::new();
+	let mut key = NibbleSlice::new(key);
+	let mut hash = *root;
+
+	let value = 'nodes: loop {
+		let mut data = match nodes.get(&hash) {
+			Some(data) => *data,
+			None if hash == RlpCodec::HASHED_NULL_NODE => break None,
+			None => return Err(ProofError::MissingNode(hash)),
+		};
+		used.insert(hash);
+
+		// walk through the node and any children inlined into it.
+		loop {
+			let child = match RlpCodec::decode(data).map_err(|e| ProofError::InvalidNode(hash, e))? {
+				Node::Empty => break 'nodes None,
+				Node::Leaf(partial, value) => {
+					break 'nodes if partial == key { Some(value.to_vec()) } else { None };
+				}
+				Node::Extension(partial, child) => {
+					if !key.starts_with(&partial) {
+						break 'nodes None;
+					}
+					key = key.mid(partial.len());
+					child
+				}
+				Node::Branch(children, value) => {
+					if key.is_empty() {
+						break 'nodes value.map(|v| v.to_vec());
+					}
+					let child = children[key.at(0) as usize];
+					key = key.mid(1);
+					child
+				}
+			};
+
+			match RlpCodec::try_decode_hash(child) {
+				Some(child_hash) => {
+					hash = child_hash;
+					continue 'nodes;
+				}
+				None => data = child,
+			}
+		}
+	};
+
+	match nodes.len() - used.len() {
+		0 => Ok(value),
+		n => Err(ProofError::ExtraneousNodes(n)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use parity_bytes::Bytes;
+	use trie::TrieMut;
+	use test_helpers;
+	use {TrieDB, SecTrieDB, SecTrieDBMut};
+	use super::{prove, verify_proof, ProofError};
+
+	fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+		haystack.windows(needle.len()).any(|w| w == needle)
+	}
+
+	fn assert_linked(root: &H256, proof: &[Bytes]) {
+		assert!(!proof.is_empty());
+		assert_eq!(keccak(&proof[0]), *root);
+		for pair in proof.windows(2) {
+			assert!(contains(&pair[0], &keccak(&pair[1])));
+		}
+	}
+
+	fn populate() -> (MemoryDB<KeccakHasher>, H256) {
+		test_helpers::populate((0u8..200).map(|i| ([i, i.wrapping_mul(7)], vec![i; 40])))
+	}
+
+	#[test]
+	fn proves_present_key() {
+		let (memdb, root) = populate();
+
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let proof = prove(&t, &[10, 70]).unwrap();
+		assert!(proof.len() > 1);
+		assert_linked(&root, &proof);
+		assert!(contains(proof.last().unwrap(), &[10; 40]));
+	}
+
+	#[test]
+	fn proves_absent_key() {
+		let (memdb, root) = populate();
+
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let proof = prove(&t, &[10, 71]).unwrap();
+		assert_linked(&root, &proof);
+	}
+
+	#[test]
+	fn proves_hashed_key() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = SecTrieDBMut::new(&mut memdb, &mut root);
+			for i in 0u8..50 {
+				t.insert(&[i], &[i; 40]).unwrap();
+			}
+		}
+
+		let sec = SecTrieDB::new(&memdb, &root).unwrap();
+		let raw = TrieDB::new(&memdb, &root).unwrap();
+		let proof = prove(&sec, &[3]).unwrap();
+		assert_linked(&root, &proof);
+		assert_eq!(proof, prove(&raw, &keccak([3u8])).unwrap());
+	}
+
+	#[test]
+	fn verifies_present_and_absent_keys() {
+		let (memdb, root) = populate();
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let proof = prove(&t, &[10, 70]).unwrap();
+		assert_eq!(verify_proof(&root, &[10, 70], &proof), Ok(Some(vec![10; 40])));
+
+		let proof = prove(&t, &[10, 71]).unwrap();
+		assert_eq!(verify_proof(&root, &[10, 71], &proof), Ok(None));
+
+		let proof = prove(&t, &[10]).unwrap();
+		assert_eq!(verify_proof(&root, &[10], &proof), Ok(None));
+	}
+
+	#[test]
+	fn verifies_empty_trie() {
+		assert_eq!(verify_proof(&KECCAK_NULL_RLP, b"foo", &[]), Ok(None));
+	}
+
+	#[test]
+	fn rejects_bad_proofs() {
+		let (memdb, root) = populate();
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		let proof = prove(&t, &[10, 70]).unwrap();
+
+		// truncated path
+		let truncated = &proof[..proof.len() - 1];
+		let missing = keccak(proof.last().unwrap());
+		assert_eq!(verify_proof(&root, &[10, 70], truncated), Err(ProofError::MissingNode(missing)));
+
+		// nodes for another key
+		let mut extra = proof.clone();
+		extra.extend(prove(&t, &[200 - 1, 199u8.wrapping_mul(7)]).unwrap().into_iter().skip(1));
+		assert_eq!(verify_proof(&root, &[10, 70], &extra), Err(ProofError::ExtraneousNodes(extra.len() - proof.len())));
+
+		// tampered node
+		let mut tampered = proof.clone();
+		let last = tampered.len() - 1;
+		let value_end = tampered[last].len() - 1;
+		tampered[last][value_end] ^= 1;
+		assert_eq!(verify_proof(&root, &[10, 70], &tampered), Err(ProofError::MissingNode(missing)));
+
+		// wrong root
+		assert_eq!(verify_proof(&H256::from(1), &[10, 70], &proof), Err(ProofError::MissingNode(H256::from(1))));
+	}
+}
@@ -0,0 +1,127 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recording of the nodes visited by several trie lookups.
+
+use std::collections::HashSet;
+
+use ethereum_types::H256;
+use hashdb::DBValue;
+use keccak_hasher::KeccakHasher;
+use parity_bytes::Bytes;
+use trie::{Query, Record};
+
+/// Records every hashed node visited by any number of lookups, keeping each node once.
+///
+/// Pass `&mut WitnessRecorder` as the query of `Trie::get_with`. Nodes are kept in the order they
+/// were first visited, so for a single lookup the drained nodes form a proof accepted by
+/// `verify_proof`.
+#[derive(Debug, Default)]
+pub struct WitnessRecorder {
+	seen: HashSet<H256>,
+	records: Vec<Record<H256>>,
+}
+
+impl WitnessRecorder {
+	/// Create a new, empty recorder.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Number of distinct nodes recorded so far.
+	pub fn len(&self) -> usize {
+		self.records.len()
+	}
+
+	/// Whether no node has been recorded yet.
+	pub fn is_empty(&self) -> bool {
+		self.records.is_empty()
+	}
+
+	/// Record a node, unless it has been recorded before.
+	pub fn record(&mut self, hash: &H256, data: &[u8], depth: u32) {
+		if self.seen.insert(*hash) {
+			self.records.push(Record { hash: *hash, data: data.to_vec(), depth });
+		}
+	}
+
+	/// Drain the recorded nodes, along with their hashes and depths.
+	pub fn drain_records(&mut self) -> Vec<Record<H256>> {
+		self.seen.clear();
+		::std::mem::replace(&mut self.records, Vec::new())
+	}
+
+	/// Drain the encoded recorded nodes.
+	pub fn drain(&mut self) -> Vec<Bytes> {
+		self.drain_records().into_iter().map(|record| record.data).collect()
+	}
+}
+
+impl<'a> Query<KeccakHasher> for &'a mut WitnessRecorder {
+	type Item = DBValue;
+
+	fn decode(self, value: &[u8]) -> DBValue {
+		DBValue::from_slice(value)
+	}
+
+	fn record(&mut self, hash: &H256, data: &[u8], depth: u32) {
+		(&mut **self).record(hash, data, depth);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use keccak_hash::KECCAK_NULL_RLP;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::{Trie, TrieMut};
+	use {prove, verify_proof, TrieDB, TrieDBMut};
+	use super::WitnessRecorder;
+
+	#[test]
+	fn deduplicates_nodes_across_lookups() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for i in 0u8..100 {
+				t.insert(&[i], &[i; 40]).unwrap();
+			}
+		}
+		let t = TrieDB::new(&memdb, &root).unwrap();
+
+		let mut recorder = WitnessRecorder::new();
+		t.get_with(&[1], &mut recorder).unwrap();
+		let proof = recorder.drain();
+		assert_eq!(proof, prove(&t, &[1]).unwrap());
+		assert_eq!(verify_proof(&root, &[1], &proof), Ok(Some(vec![1; 40])));
+		assert!(recorder.is_empty());
+
+		t.get_with(&[1], &mut recorder).unwrap();
+		t.get_with(&[1], &mut recorder).unwrap();
+		assert_eq!(recorder.len(), proof.len());
+
+		t.get_with(&[2], &mut recorder).unwrap();
+		let proof_2 = prove(&t, &[2]).unwrap();
+		let shared = proof.iter().filter(|node| proof_2.contains(node)).count();
+		assert!(shared >= 1);
+		assert_eq!(recorder.len(), proof.len() + proof_2.len() - shared);
+
+		let records = recorder.drain_records();
+		assert_eq!(records[0].hash, root);
+		assert_eq!(records[0].depth, 0);
+	}
+}
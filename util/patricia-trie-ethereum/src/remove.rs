@@ -0,0 +1,318 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Removal of every key under a prefix from a Keccak/Rlp-flavoured trie.
+
+use elastic_array::ElasticArray1024;
+use ethereum_types::H256;
+use hashdb::{HashDB, DBValue, Hasher};
+use keccak_hasher::KeccakHasher;
+use trie::{TrieError, NodeCodec, ChildReference, node::Node};
+
+use hex_prefix::hex_prefix_encode;
+use iterator::{Walker, NodeRef, to_nibbles, extend};
+use {RlpCodec, Result};
+
+/// Remove every key starting with `prefix` from the trie with the given `root`, updating `root`
+/// in place. An empty prefix clears the trie.
+///
+/// Rather than removing the keys one by one, the subtree at the prefix boundary is detached as a
+/// whole: its nodes are removed from `db` and only the nodes on the path from the root to the
+/// boundary are rewritten. Pending changes of a `TrieDBMut` over the same database must be
+/// committed first.
+pub fn remove_prefix(db: &mut HashDB<KeccakHasher>, root: &mut H256, prefix: &[u8]) -> Result<()> {
+	if *root == RlpCodec::HASHED_NULL_NODE {
+		return Ok(());
+	}
+
+	let mut remover = Remover { db };
+	match remover.remove(NodeRef::Hash(*root), &to_nibbles(prefix), *root)? {
+		None => {},
+		Some(None) => *root = RlpCodec::HASHED_NULL_NODE,
+		// the root is always stored by hash, however small it is.
+		Some(Some(node)) => *root = remover.db.insert(&encode(&node)),
+	}
+	Ok(())
+}
+
+/// Node rewritten by the removal, not yet stored. Partial keys are in nibbles.
+enum Rewritten {
+	Leaf(Vec<u8>, DBValue),
+	Extension(Vec<u8>, NodeRef),
+	Branch(Vec<Option<NodeRef>>, Option<DBValue>),
+}
+
+/// Outcome of removing a prefix from a subtree: `None` if the subtree is untouched, `Some(None)`
+/// if it is gone entirely, otherwise the node replacing its top node.
+type Outcome = Option<Option<Rewritten>>;
+
+struct Remover<'a> {
+	db: &'a mut HashDB<KeccakHasher>,
+}
+
+impl<'a> Remover<'a> {
+	fn fetch(&self, node: &NodeRef) -> Result<DBValue> {
+		match *node {
+			NodeRef::Hash(ref hash) => self.db.get(hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(*hash))),
+			NodeRef::Inline(ref data) => Ok(data.clone()),
+		}
+	}
+
+	/// Remove `node` from the database, unless it is inlined into its parent.
+	fn kill(&mut self, node: &NodeRef) {
+		if let NodeRef::Hash(ref hash) = *node {
+			self.db.remove(hash);
+		}
+	}
+
+	/// Remove `node` and every node below it from the database.
+	fn kill_subtree(&mut self, node: NodeRef, parent: H256) -> Result<()> {
+		let mut hashes = Vec::new();
+		for visited in Walker::subtree(&*self.db, node, Vec::new(), parent) {
+			if let Some(hash) = visited?.hash {
+				hashes.push(hash);
+			}
+		}
+		for hash in hashes {
+			self.db.remove(&hash);
+		}
+		Ok(())
+	}
+
+	/// Store a rewritten node, inlining it if it is small enough.
+	fn store(&mut self, node: &Rewritten) -> NodeRef {
+		let encoded = encode(node);
+		match encoded.len() >= KeccakHasher::LENGTH {
+			true => NodeRef::Hash(self.db.insert(&encoded)),
+			false => NodeRef::Inline(DBValue::from_slice(&encoded)),
+		}
+	}
+
+	/// Remove the keys under the nibble `prefix` from the subtree at `node`.
+	fn remove(&mut self, node: NodeRef, prefix: &[u8], parent: H256) -> Result<Outcome> {
+		if prefix.is_empty() {
+			self.kill_subtree(node, parent)?;
+			return Ok(Some(None));
+		}
+
+		let hash = match node {
+			NodeRef::Hash(ref hash) => *hash,
+			NodeRef::Inline(_) => parent,
+		};
+		let data = self.fetch(&node)?;
+		let outcome = match RlpCodec::decode(&data).map_err(|e| Box::new(TrieError::DecoderError(hash, e)))? {
+			Node::Empty => None,
+			Node::Leaf(partial, _) => match extend(&[], &partial).starts_with(prefix) {
+				true => Some(None),
+				false => None,
+			},
+			Node::Extension(partial, child) => {
+				let partial = extend(&[], &partial);
+				let child = NodeRef::from_encoded(child);
+				if partial.starts_with(prefix) {
+					// the prefix ends inside the extension.
+					self.kill_subtree(child, hash)?;
+					Some(None)
+				} else if prefix.starts_with(&partial) {
+					match self.remove(child, &prefix[partial.len()..], hash)? {
+						Some(Some(child)) => Some(Some(self.prepend(partial, child))),
+						outcome => outcome,
+					}
+				} else {
+					None
+				}
+			},
+			Node::Branch(children, value) => {
+				let index = prefix[0] as usize;
+				match RlpCodec::is_empty_node(children[index]) {
+					true => None,
+					false => match self.remove(NodeRef::from_encoded(children[index]), &prefix[1..], hash)? {
+						None => None,
+						Some(child) => {
+							let mut children: Vec<_> = children.iter()
+								.map(|child| match RlpCodec::is_empty_node(child) {
+									true => None,
+									false => Some(NodeRef::from_encoded(child)),
+								})
+								.collect();
+							children[index] = None;
+							Some(self.fix_branch(children, value.map(DBValue::from_slice), index, child, hash)?)
+						},
+					},
+				}
+			},
+		};
+
+		if outcome.is_some() {
+			self.kill(&node);
+		}
+		Ok(outcome)
+	}
+
+	/// Rebuild a branch whose child at `index` was replaced by `child`, collapsing it if it no
+	/// longer has at least two children, or one child and a value.
+	fn fix_branch(&mut self, mut children: Vec<Option<NodeRef>>, value: Option<DBValue>, index: usize, child: Option<Rewritten>, hash: H256) -> Result<Option<Rewritten>> {
+		let others = children.iter().filter(|child| child.is_some()).count();
+		match (others, child, value) {
+			(0, None, None) => Ok(None),
+			(0, None, Some(value)) => Ok(Some(Rewritten::Leaf(Vec::new(), value))),
+			(0, Some(child), None) => Ok(Some(self.prepend(vec![index as u8], child))),
+			(1, None, None) => {
+				let other = children.iter().position(|child| child.is_some()).expect("one child is left; qed");
+				let child = children[other].take().expect("position returned a child; qed");
+				self.absorb(vec![other as u8], child, hash).map(Some)
+			},
+			(_, child, value) => {
+				if let Some(child) = child {
+					children[index] = Some(self.store(&child));
+				}
+				Ok(Some(Rewritten::Branch(children, value)))
+			},
+		}
+	}
+
+	/// Prefix the partial key of a rewritten node with `partial`.
+	fn prepend(&mut self, mut partial: Vec<u8>, node: Rewritten) -> Rewritten {
+		match node {
+			Rewritten::Leaf(rest, value) => {
+				partial.extend(rest);
+				Rewritten::Leaf(partial, value)
+			},
+			Rewritten::Extension(rest, child) => {
+				partial.extend(rest);
+				Rewritten::Extension(partial, child)
+			},
+			branch => Rewritten::Extension(partial, self.store(&branch)),
+		}
+	}
+
+	/// Prefix the partial key of a stored node with `partial`, merging it into a single node if
+	/// it is a leaf or an extension.
+	fn absorb(&mut self, partial: Vec<u8>, node: NodeRef, parent: H256) -> Result<Rewritten> {
+		let hash = match node {
+			NodeRef::Hash(ref hash) => *hash,
+			NodeRef::Inline(_) => parent,
+		};
+		let data = self.fetch(&node)?;
+		let merged = match RlpCodec::decode(&data).map_err(|e| Box::new(TrieError::DecoderError(hash, e)))? {
+			Node::Leaf(rest, value) => Rewritten::Leaf(extend(&partial, &rest), DBValue::from_slice(value)),
+			Node::Extension(rest, child) => Rewritten::Extension(extend(&partial, &rest), NodeRef::from_encoded(child)),
+			Node::Branch(..) | Node::Empty => return Ok(Rewritten::Extension(partial, node)),
+		};
+		self.kill(&node);
+		Ok(merged)
+	}
+}
+
+fn encode(node: &Rewritten) -> ElasticArray1024<u8> {
+	match *node {
+		Rewritten::Leaf(ref partial, ref value) => RlpCodec::leaf_node(&hex_prefix_encode(partial, true), value),
+		Rewritten::Extension(ref partial, ref child) => RlpCodec::ext_node(&hex_prefix_encode(partial, false), child_reference(child)),
+		Rewritten::Branch(ref children, ref value) => RlpCodec::branch_node(children.iter().map(|child| child.as_ref().map(child_reference)), value.clone()),
+	}
+}
+
+fn child_reference(node: &NodeRef) -> ChildReference<H256> {
+	match *node {
+		NodeRef::Hash(hash) => ChildReference::Hash(hash),
+		NodeRef::Inline(ref data) => {
+			let mut inline = H256::new();
+			inline[..data.len()].copy_from_slice(data);
+			ChildReference::Inline(inline, data.len())
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use {assert_clean, TrieDBMut};
+	use super::remove_prefix;
+
+	fn keys() -> Vec<Vec<u8>> {
+		let mut keys: Vec<Vec<u8>> = (0..200u32).map(|i| keccak(&[i as u8])[..3].to_vec()).collect();
+		keys.extend([&b"abcdef1"[..], b"abcdef2", b"abcdef3", b"b", b"do", b"dog", b"doge", b"horse"].iter().map(|k| k.to_vec()));
+		keys
+	}
+
+	/// Remove `prefix` both through `remove_prefix` and key by key through `TrieDBMut`, and check
+	/// that both leave the same root and the same database behind.
+	fn check(prefix: &[u8], value_len: usize) {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for key in keys() {
+				t.insert(&key, &vec![key[0]; value_len]).unwrap();
+			}
+		}
+
+		let mut expected_db = memdb.clone();
+		let mut expected_root = root;
+		{
+			let mut t = TrieDBMut::from_existing(&mut expected_db, &mut expected_root).unwrap();
+			for key in keys().into_iter().filter(|key| key.starts_with(prefix)) {
+				t.remove(&key).unwrap();
+			}
+		}
+
+		remove_prefix(&mut memdb, &mut root, prefix).unwrap();
+		assert_eq!(root, expected_root);
+		assert_clean(&expected_db, &[expected_root]);
+		assert_clean(&memdb, &[root]);
+	}
+
+	#[test]
+	fn matches_removing_keys_one_by_one() {
+		for &value_len in &[1, 40] {
+			check(b"do", value_len);
+			check(&keys()[17][..1], value_len);
+			check(b"zzz", value_len);
+		}
+	}
+
+	#[test]
+	fn prefix_ending_inside_extension() {
+		check(b"abc", 1);
+		check(b"abc", 40);
+	}
+
+	#[test]
+	fn prefix_matching_single_leaf() {
+		check(b"abcdef2", 1);
+		check(b"horse", 40);
+	}
+
+	#[test]
+	fn empty_prefix_clears_trie() {
+		check(b"", 40);
+
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			for key in keys() {
+				t.insert(&key, &key).unwrap();
+			}
+		}
+		remove_prefix(&mut memdb, &mut root, b"").unwrap();
+		assert_eq!(root, KECCAK_NULL_RLP);
+		assert_clean(&memdb, &[]);
+	}
+}
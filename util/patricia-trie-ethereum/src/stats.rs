@@ -0,0 +1,130 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics about the shape of Keccak/Rlp-flavoured tries.
+
+use trie::Trie;
+
+use iterator::{Walker, Direction, NodeKind};
+use {TrieDB, Result};
+
+/// Statistics about the shape of a trie.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrieStatistics {
+	/// Number of leaf nodes.
+	pub leaves: usize,
+	/// Number of extension nodes.
+	pub extensions: usize,
+	/// Number of branch nodes.
+	pub branches: usize,
+	/// Number of nodes small enough to be inlined into their parent.
+	pub inline_nodes: usize,
+	/// Number of values, whether held by leaves or branches.
+	pub values: usize,
+	/// Total size of the values, in bytes.
+	pub value_bytes: usize,
+	/// Number of values held by nodes at each depth, the root being at depth 0.
+	pub depth_histogram: Vec<usize>,
+}
+
+/// Gather statistics about `trie` in a single walk over its nodes.
+pub fn statistics<'db>(trie: &'db TrieDB<'db>) -> Result<TrieStatistics> {
+	let mut stats = TrieStatistics::default();
+	for node in Walker::new(trie.db(), trie.root(), Vec::new(), Direction::Forward) {
+		let node = node?;
+		match node.kind {
+			NodeKind::Empty => {},
+			NodeKind::Leaf => stats.leaves += 1,
+			NodeKind::Extension => stats.extensions += 1,
+			NodeKind::Branch => stats.branches += 1,
+		}
+
+		if node.hash.is_none() {
+			stats.inline_nodes += 1;
+		}
+
+		if let Some((_, value)) = node.value {
+			stats.values += 1;
+			stats.value_bytes += value.len();
+			if stats.depth_histogram.len() <= node.depth {
+				stats.depth_histogram.resize(node.depth + 1, 0);
+			}
+			stats.depth_histogram[node.depth] += 1;
+		}
+	}
+
+	Ok(stats)
+}
+
+/// Count the keys of `trie`.
+///
+/// Tries don't keep track of the number of keys they hold, so this walks over every node of the
+/// trie. Use `Trie::is_empty` to merely check whether there are any keys.
+pub fn count_slow<'db>(trie: &'db TrieDB<'db>) -> Result<usize> {
+	let mut count = 0;
+	for node in Walker::new(trie.db(), trie.root(), Vec::new(), Direction::Forward) {
+		if node?.value.is_some() {
+			count += 1;
+		}
+	}
+	Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+	use keccak_hash::KECCAK_NULL_RLP;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use trie::TrieMut;
+	use {TrieDB, TrieDBMut};
+	use super::{statistics, count_slow, TrieStatistics};
+
+	#[test]
+	fn statistics_of_known_trie() {
+		let mut memdb = MemoryDB::<KeccakHasher>::new();
+		let mut root = KECCAK_NULL_RLP;
+		{
+			let mut t = TrieDBMut::new(&mut memdb, &mut root);
+			t.insert(b"do", b"verb").unwrap();
+			t.insert(b"dog", b"puppy").unwrap();
+			t.insert(b"doge", b"coin").unwrap();
+			t.insert(b"horse", b"stallion").unwrap();
+		}
+
+		// ext(6) -> branch -+- 4: ext(6f) -> branch("verb") -> 6: ext(7) -> branch("puppy") -> 6: leaf(5, "coin")
+		//                   +- 8: leaf(6f727365, "stallion")
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(statistics(&t).unwrap(), TrieStatistics {
+			leaves: 2,
+			extensions: 3,
+			branches: 3,
+			inline_nodes: 4,
+			values: 4,
+			value_bytes: 21,
+			depth_histogram: vec![0, 0, 1, 1, 0, 1, 1],
+		});
+		assert_eq!(count_slow(&t).unwrap(), 4);
+	}
+
+	#[test]
+	fn statistics_of_empty_trie() {
+		let memdb = MemoryDB::<KeccakHasher>::new();
+		let root = KECCAK_NULL_RLP;
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(statistics(&t).unwrap(), TrieStatistics::default());
+		assert_eq!(count_slow(&t).unwrap(), 0);
+	}
+}
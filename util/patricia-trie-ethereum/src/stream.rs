@@ -0,0 +1,516 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Export and import of a Keccak/Rlp-flavoured trie as a stream of nodes.
+//!
+//! The stream is a sequence of records, each made of its length as a big-endian `u32` followed by
+//! that many bytes of RLP. The first record, `[root, preimages]`, holds the root and whether the
+//! stream carries `FatDB` preimages. It is followed by one `[0, node]` record per hashed node, in
+//! the order of a forward walk over the trie, and, if preimages are included, by one
+//! `[1, preimage]` record after the node holding each key. A final `[2, count]` record, `count`
+//! being the number of node and preimage records, marks the end of the stream.
+
+use std::{error, fmt, io, mem};
+use std::io::{Read, Write};
+
+use ethereum_types::H256;
+use hashdb::{HashDB, DBValue, Hasher};
+use keccak_hasher::KeccakHasher;
+use rlp::{Rlp, RlpStream, DecoderError};
+use trie::{NodeCodec, node::Node};
+
+use iterator::{Walker, Direction, NodeRef, extend, to_bytes};
+use {RlpCodec, TrieError};
+
+const NODE: u8 = 0;
+const PREIMAGE: u8 = 1;
+const END: u8 = 2;
+
+/// Maximum length of a record, far larger than any trie node.
+const MAX_RECORD_LEN: usize = 16 * 1024 * 1024;
+
+/// Number of nodes and preimages `import_state` writes to the destination at once.
+pub const IMPORT_BATCH: usize = 4096;
+
+/// Error encountered while exporting or importing a trie stream.
+#[derive(Debug)]
+pub enum StreamError {
+	/// Reading or writing the stream failed.
+	Io(io::Error),
+	/// A node of the trie being exported could not be read.
+	Trie(Box<TrieError>),
+	/// A record could not be decoded.
+	Decoder(DecoderError),
+	/// A record is not part of the format.
+	InvalidRecord,
+	/// The stream ended before its end marker, or records are missing.
+	Truncated,
+	/// The given nodes of the imported trie are missing from the stream or malformed.
+	Incomplete(Vec<H256>),
+	/// The preimage of the given key is missing from the stream or the database, or doesn't hash
+	/// to the key.
+	Preimage(H256),
+}
+
+impl fmt::Display for StreamError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			StreamError::Io(ref err) => write!(f, "Trie stream I/O error: {}", err),
+			StreamError::Trie(ref err) => write!(f, "Trie stream export failed: {}", err),
+			StreamError::Decoder(ref err) => write!(f, "Trie stream record is malformed: {}", err),
+			StreamError::InvalidRecord => write!(f, "Trie stream contains an invalid record"),
+			StreamError::Truncated => write!(f, "Trie stream is truncated"),
+			StreamError::Incomplete(ref nodes) => write!(f, "Trie stream is missing {} nodes", nodes.len()),
+			StreamError::Preimage(ref key) => write!(f, "Trie stream has no valid preimage for key {:x}", key),
+		}
+	}
+}
+
+impl error::Error for StreamError {
+	fn description(&self) -> &str {
+		"Trie stream error"
+	}
+}
+
+impl From<io::Error> for StreamError {
+	fn from(err: io::Error) -> Self {
+		match err.kind() {
+			io::ErrorKind::UnexpectedEof => StreamError::Truncated,
+			_ => StreamError::Io(err),
+		}
+	}
+}
+
+impl From<DecoderError> for StreamError {
+	fn from(err: DecoderError) -> Self {
+		StreamError::Decoder(err)
+	}
+}
+
+impl From<Box<TrieError>> for StreamError {
+	fn from(err: Box<TrieError>) -> Self {
+		StreamError::Trie(err)
+	}
+}
+
+/// Write every node reachable from `root` in the trie held by `db` to `out`.
+///
+/// `FatDB` preimages are included if `db` holds the one of the first key of the trie, in which
+/// case it must hold the preimages of all the keys.
+pub fn export_state(db: &HashDB<KeccakHasher>, root: &H256, out: &mut Write) -> Result<(), StreamError> {
+	let mut preimages = false;
+	for node in Walker::new(db, root, Vec::new(), Direction::Forward) {
+		if let Some((key, _)) = node?.value {
+			preimages = hashed_key(&key).map_or(false, |key| db.contains(&KeccakHasher::hash(&key)));
+			break;
+		}
+	}
+
+	let mut header = RlpStream::new_list(2);
+	header.append(root).append(&preimages);
+	write_record(out, &header.out())?;
+
+	let mut count = 0u64;
+	for node in Walker::new(db, root, Vec::new(), Direction::Forward) {
+		let node = node?;
+		if node.hash.is_some() {
+			write_entry(out, NODE, &node.data)?;
+			count += 1;
+		}
+
+		if let (true, Some(key)) = (preimages, node.value.and_then(|(key, _)| hashed_key(&key))) {
+			let preimage = db.get(&KeccakHasher::hash(&key)).ok_or(StreamError::Preimage(key))?;
+			write_entry(out, PREIMAGE, &preimage)?;
+			count += 1;
+		}
+	}
+
+	let mut end = RlpStream::new_list(2);
+	end.append(&END).append(&count);
+	write_record(out, &end.out())?;
+	Ok(())
+}
+
+/// Entries of a stream read by `import_state_batched`, keyed as a `FatDB` stores them.
+#[derive(Debug, Default)]
+pub struct ImportBatch {
+	/// Nodes, under their hashes.
+	pub nodes: Vec<(H256, DBValue)>,
+	/// Preimages, under the hash of the key they are the preimage of.
+	pub preimages: Vec<(H256, DBValue)>,
+}
+
+impl ImportBatch {
+	/// Number of entries in the batch.
+	pub fn len(&self) -> usize {
+		self.nodes.len() + self.preimages.len()
+	}
+}
+
+/// Read a stream written by `export_state` into `dest`, returning the root of the trie.
+///
+/// See `import_state_batched`, which this calls with batches of `IMPORT_BATCH` entries.
+pub fn import_state(input: &mut Read, dest: &mut HashDB<KeccakHasher>) -> Result<H256, StreamError> {
+	import_state_batched(input, IMPORT_BATCH, &mut |batch| {
+		for (key, value) in batch.nodes.into_iter().chain(batch.preimages) {
+			dest.emplace(key, value);
+		}
+		Ok(())
+	})
+}
+
+/// Read a stream written by `export_state`, handing its nodes and preimages to `commit` in
+/// batches of at most `batch_len` entries. Returns the root of the trie.
+///
+/// The stream is checked as it is read, by walking the trie in the order it was exported in:
+/// each node must hash to the reference it is reached through and each preimage must hash to
+/// the key it follows. Only checked entries are committed, but an import failing part way
+/// through leaves the batches committed until then in place.
+pub fn import_state_batched(
+	input: &mut Read,
+	batch_len: usize,
+	commit: &mut FnMut(ImportBatch) -> io::Result<()>,
+) -> Result<H256, StreamError> {
+	let header = read_record(input)?;
+	let header = Rlp::new(&header);
+	if header.item_count()? != 2 {
+		return Err(StreamError::InvalidRecord);
+	}
+	let root: H256 = header.val_at(0)?;
+	let preimages: bool = header.val_at(1)?;
+
+	let mut stack = Vec::new();
+	if root != RlpCodec::HASHED_NULL_NODE {
+		stack.push((NodeRef::Hash(root), Vec::new(), root));
+	}
+
+	let mut batch = ImportBatch::default();
+	let mut count = 0u64;
+	while let Some((node, path, parent)) = stack.pop() {
+		let (data, parent) = match node {
+			NodeRef::Hash(hash) => {
+				let data = match read_entry(input)? {
+					Entry::Node(data) => data,
+					Entry::Preimage(_) => return Err(StreamError::Incomplete(vec![hash])),
+					Entry::End(_) => return Err(StreamError::Truncated),
+				};
+				if KeccakHasher::hash(&data) != hash {
+					return Err(StreamError::Incomplete(vec![hash]));
+				}
+				let data = DBValue::from_vec(data);
+				batch.nodes.push((hash, data.clone()));
+				count += 1;
+				(data, hash)
+			},
+			NodeRef::Inline(data) => (data, parent),
+		};
+
+		let key = match RlpCodec::decode(&data).map_err(|_| StreamError::Incomplete(vec![parent]))? {
+			Node::Empty => None,
+			Node::Leaf(partial, _) => Some(extend(&path, &partial)),
+			Node::Extension(partial, child) => {
+				stack.push((NodeRef::from_encoded(child), extend(&path, &partial), parent));
+				None
+			},
+			Node::Branch(nodes, value) => {
+				for (i, child) in nodes.iter().enumerate().rev().filter(|&(_, child)| !RlpCodec::is_empty_node(child)) {
+					let mut child_path = path.clone();
+					child_path.push(i as u8);
+					stack.push((NodeRef::from_encoded(child), child_path, parent));
+				}
+				value.map(|_| path)
+			},
+		};
+
+		if let (true, Some(key)) = (preimages, key.and_then(|key| hashed_key(&key))) {
+			let preimage = match read_entry(input)? {
+				Entry::Preimage(preimage) => preimage,
+				Entry::Node(_) => return Err(StreamError::Preimage(key)),
+				Entry::End(_) => return Err(StreamError::Truncated),
+			};
+			if KeccakHasher::hash(&preimage) != key {
+				return Err(StreamError::Preimage(key));
+			}
+			batch.preimages.push((KeccakHasher::hash(&key), DBValue::from_vec(preimage)));
+			count += 1;
+		}
+
+		if batch.len() >= batch_len {
+			commit(mem::replace(&mut batch, ImportBatch::default()))?;
+		}
+	}
+
+	match read_entry(input)? {
+		Entry::End(expected) if expected == count => {},
+		Entry::End(_) => return Err(StreamError::Truncated),
+		_ => return Err(StreamError::InvalidRecord),
+	}
+	if batch.len() > 0 {
+		commit(batch)?;
+	}
+	Ok(root)
+}
+
+/// Key of the value stored under `nibbles`, if it is long enough to be the hash of a preimage.
+fn hashed_key(nibbles: &[u8]) -> Option<H256> {
+	let key = to_bytes(nibbles);
+	match key.len() == KeccakHasher::LENGTH {
+		true => Some(H256::from_slice(&key)),
+		false => None,
+	}
+}
+
+/// A record following the header of a stream.
+enum Entry {
+	Node(Vec<u8>),
+	Preimage(Vec<u8>),
+	End(u64),
+}
+
+fn read_entry(input: &mut Read) -> Result<Entry, StreamError> {
+	let record = read_record(input)?;
+	let record = Rlp::new(&record);
+	if record.item_count()? != 2 {
+		return Err(StreamError::InvalidRecord);
+	}
+
+	match record.val_at::<u8>(0)? {
+		NODE => Ok(Entry::Node(record.at(1)?.data()?.to_vec())),
+		PREIMAGE => Ok(Entry::Preimage(record.at(1)?.data()?.to_vec())),
+		END => Ok(Entry::End(record.val_at(1)?)),
+		_ => Err(StreamError::InvalidRecord),
+	}
+}
+
+fn write_entry(out: &mut Write, tag: u8, data: &[u8]) -> io::Result<()> {
+	let mut entry = RlpStream::new_list(2);
+	entry.append(&tag).append(&data);
+	write_record(out, &entry.out())
+}
+
+fn write_record(out: &mut Write, record: &[u8]) -> io::Result<()> {
+	let len = record.len();
+	out.write_all(&[(len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8])?;
+	out.write_all(record)
+}
+
+fn read_record(input: &mut Read) -> Result<Vec<u8>, StreamError> {
+	let mut len = [0u8; 4];
+	input.read_exact(&mut len)?;
+	let len = (len[0] as usize) << 24 | (len[1] as usize) << 16 | (len[2] as usize) << 8 | len[3] as usize;
+	if len > MAX_RECORD_LEN {
+		return Err(StreamError::InvalidRecord);
+	}
+
+	let mut record = vec![0; len];
+	input.read_exact(&mut record)?;
+	Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+	use hashdb::{HashDB, Hasher};
+	use keccak_hash::KECCAK_NULL_RLP;
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use rlp::{Rlp, RlpStream};
+	use trie::Trie;
+	use test_helpers;
+	use {FatDB, TrieDB};
+	use super::{export_state, import_state, import_state_batched, read_record, write_record, ImportBatch, StreamError, NODE, PREIMAGE};
+
+	fn populate() -> (MemoryDB<KeccakHasher>, ::ethereum_types::H256) {
+		test_helpers::populate_fat((0..200u32).map(|i| ([i as u8, (i >> 8) as u8, 1], vec![i as u8; 1 + i as usize % 50])))
+	}
+
+	fn export(memdb: &MemoryDB<KeccakHasher>, root: &::ethereum_types::H256) -> Vec<u8> {
+		let mut stream = Vec::new();
+		export_state(memdb, root, &mut stream).unwrap();
+		stream
+	}
+
+	fn records(mut stream: &[u8]) -> Vec<Vec<u8>> {
+		let mut records = Vec::new();
+		while !stream.is_empty() {
+			records.push(read_record(&mut stream).unwrap());
+		}
+		records
+	}
+
+	fn join(records: &[Vec<u8>]) -> Vec<u8> {
+		let mut stream = Vec::new();
+		for record in records {
+			write_record(&mut stream, record).unwrap();
+		}
+		stream
+	}
+
+	fn position(records: &[Vec<u8>], tag: u8) -> usize {
+		records.iter().skip(1).position(|record| Rlp::new(record).val_at::<u8>(0) == Ok(tag)).unwrap() + 1
+	}
+
+	#[test]
+	fn round_trip() {
+		let (memdb, root) = populate();
+		let stream = export(&memdb, &root);
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		assert_eq!(import_state(&mut &stream[..], &mut dest).unwrap(), root);
+
+		let expected: Vec<_> = FatDB::new(&memdb, &root).unwrap().iter().unwrap().map(|item| item.unwrap()).collect();
+		let items: Vec<_> = FatDB::new(&dest, &root).unwrap().iter().unwrap().map(|item| item.unwrap()).collect();
+		assert_eq!(items, expected);
+	}
+
+	#[test]
+	fn round_trip_without_preimages() {
+		let (memdb, root) = test_helpers::populate((0..200u32).map(|i| ([i as u8, (i >> 8) as u8, 1], vec![i as u8; 1 + i as usize % 50])));
+		let stream = export(&memdb, &root);
+		assert!(!records(&stream).iter().any(|record| Rlp::new(record).val_at::<u8>(0) == Ok(PREIMAGE)));
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		assert_eq!(import_state(&mut &stream[..], &mut dest).unwrap(), root);
+
+		let expected: Vec<_> = TrieDB::new(&memdb, &root).unwrap().iter().unwrap().map(|item| item.unwrap()).collect();
+		let items: Vec<_> = TrieDB::new(&dest, &root).unwrap().iter().unwrap().map(|item| item.unwrap()).collect();
+		assert_eq!(items, expected);
+	}
+
+	#[test]
+	fn round_trip_empty_trie() {
+		let memdb = MemoryDB::<KeccakHasher>::new();
+		let stream = export(&memdb, &KECCAK_NULL_RLP);
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		assert_eq!(import_state(&mut &stream[..], &mut dest).unwrap(), KECCAK_NULL_RLP);
+		assert!(dest.keys().is_empty());
+	}
+
+	#[test]
+	fn imports_in_batches() {
+		let (memdb, root) = populate();
+		let stream = export(&memdb, &root);
+
+		let mut batches = Vec::new();
+		assert_eq!(import_state_batched(&mut &stream[..], 10, &mut |batch| { batches.push(batch); Ok(()) }).unwrap(), root);
+		assert!(batches.len() > 1);
+		assert!(batches.iter().all(|batch| batch.len() > 0 && batch.len() <= 10));
+		assert_eq!(batches.iter().map(ImportBatch::len).sum::<usize>(), records(&stream).len() - 2);
+		assert!(batches.iter().flat_map(|batch| batch.nodes.iter().chain(&batch.preimages)).all(|&(ref key, ref value)| memdb.get(key).as_ref() == Some(value)));
+
+		// the batches committed before a corrupt node are all from the original trie.
+		let mut records = records(&stream);
+		let index = records.iter().rposition(|record| Rlp::new(record).val_at::<u8>(0) == Ok(NODE)).unwrap();
+		*records[index].last_mut().unwrap() ^= 0x01;
+
+		let mut batches = Vec::new();
+		match import_state_batched(&mut &join(&records)[..], 10, &mut |batch| { batches.push(batch); Ok(()) }) {
+			Err(StreamError::Incomplete(ref nodes)) if !nodes.is_empty() => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+		assert!(!batches.is_empty());
+		assert!(batches.iter().flat_map(|batch| batch.nodes.iter().chain(&batch.preimages)).all(|&(ref key, ref value)| memdb.get(key).as_ref() == Some(value)));
+	}
+
+	#[test]
+	fn rejects_truncated_stream() {
+		let (memdb, root) = populate();
+		let stream = export(&memdb, &root);
+
+		for &len in &[0, 3, stream.len() / 2, stream.len() - 1] {
+			let mut dest = MemoryDB::<KeccakHasher>::new();
+			match import_state(&mut &stream[..len], &mut dest) {
+				Err(StreamError::Truncated) => {},
+				other => panic!("unexpected result: {:?}", other),
+			}
+			assert!(dest.keys().is_empty());
+		}
+
+		// dropping the last record before the end marker.
+		let mut records = records(&stream);
+		let last = records.len() - 2;
+		records.remove(last);
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		match import_state(&mut &join(&records)[..], &mut dest) {
+			Err(StreamError::Truncated) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		// dropping the root node.
+		records.remove(1);
+		match import_state(&mut &join(&records)[..], &mut dest) {
+			Err(StreamError::Incomplete(ref nodes)) if nodes == &[root] => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rejects_corrupt_nodes() {
+		let (memdb, root) = populate();
+		let stream = export(&memdb, &root);
+
+		// flip a byte inside the last node record.
+		let mut records = records(&stream);
+		let index = records.iter().rposition(|record| Rlp::new(record).val_at::<u8>(0) == Ok(NODE)).unwrap();
+		*records[index].last_mut().unwrap() ^= 0x01;
+
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		match import_state(&mut &join(&records)[..], &mut dest) {
+			Err(StreamError::Incomplete(ref nodes)) if !nodes.is_empty() => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+		assert!(dest.keys().is_empty());
+	}
+
+	#[test]
+	fn rejects_wrong_preimages() {
+		let (memdb, root) = populate();
+		let records = records(&export(&memdb, &root));
+		let first = position(&records, PREIMAGE);
+		let key = KeccakHasher::hash(Rlp::new(&records[first]).at(1).unwrap().data().unwrap());
+
+		// a preimage of another key.
+		let mut swapped = records.clone();
+		let second = first + position(&records[first..], PREIMAGE);
+		swapped.swap(first, second);
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		match import_state(&mut &join(&swapped)[..], &mut dest) {
+			Err(StreamError::Preimage(ref wrong)) if *wrong == key => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		// no preimage at all.
+		let mut missing = records.clone();
+		missing.remove(first);
+		match import_state(&mut &join(&missing)[..], &mut dest) {
+			Err(StreamError::Preimage(ref wrong)) if *wrong == key => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_records() {
+		let mut dest = MemoryDB::<KeccakHasher>::new();
+		let mut header = RlpStream::new_list(2);
+		header.append(&KECCAK_NULL_RLP).append(&false);
+		let stream = join(&[header.out(), vec![0xc2, 0x07, 0x80]]);
+		match import_state(&mut &stream[..], &mut dest) {
+			Err(StreamError::InvalidRecord) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+}
@@ -0,0 +1,91 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fixtures shared by the tests of this crate.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use ethereum_types::H256;
+use hashdb::{HashDB, AsHashDB, DBValue};
+use keccak_hash::KECCAK_NULL_RLP;
+use keccak_hasher::KeccakHasher;
+use memorydb::MemoryDB;
+use trie::TrieSpec;
+use TrieFactory;
+
+/// Database recording which nodes were fetched from it.
+pub struct FetchLog {
+	pub db: MemoryDB<KeccakHasher>,
+	pub fetched: Mutex<HashSet<H256>>,
+}
+
+impl FetchLog {
+	pub fn new(db: MemoryDB<KeccakHasher>) -> Self {
+		FetchLog { db, fetched: Mutex::new(HashSet::new()) }
+	}
+}
+
+impl HashDB<KeccakHasher> for FetchLog {
+	fn keys(&self) -> HashMap<H256, i32> { self.db.keys() }
+	fn get(&self, key: &H256) -> Option<DBValue> {
+		self.fetched.lock().unwrap().insert(*key);
+		self.db.get(key)
+	}
+	fn contains(&self, key: &H256) -> bool { self.db.contains(key) }
+	fn insert(&mut self, value: &[u8]) -> H256 { self.db.insert(value) }
+	fn emplace(&mut self, key: H256, value: DBValue) { self.db.emplace(key, value) }
+	fn remove(&mut self, key: &H256) { self.db.remove(key) }
+}
+
+impl AsHashDB<KeccakHasher> for FetchLog {
+	fn as_hashdb(&self) -> &HashDB<KeccakHasher> { self }
+	fn as_hashdb_mut(&mut self) -> &mut HashDB<KeccakHasher> { self }
+}
+
+/// Database holding a trie of `items`, along with the trie root.
+pub fn populate<I, K, V>(items: I) -> (MemoryDB<KeccakHasher>, H256)
+	where I: IntoIterator<Item = (K, V)>, K: AsRef<[u8]>, V: AsRef<[u8]>
+{
+	build(items, TrieSpec::Generic)
+}
+
+/// Same as `populate`, but the trie is built by a `FatDBMut` which also records the key preimages.
+pub fn populate_fat<I, K, V>(items: I) -> (MemoryDB<KeccakHasher>, H256)
+	where I: IntoIterator<Item = (K, V)>, K: AsRef<[u8]>, V: AsRef<[u8]>
+{
+	build(items, TrieSpec::Fat)
+}
+
+/// Trie mapping `[i, i]` to `[i; 40]` for `i` in `0..100`, deep enough to have branches,
+/// extensions and hashed leaves.
+pub fn sample_trie() -> (MemoryDB<KeccakHasher>, H256) {
+	populate((0u8..100).map(|i| ([i, i], vec![i; 40])))
+}
+
+fn build<I, K, V>(items: I, spec: TrieSpec) -> (MemoryDB<KeccakHasher>, H256)
+	where I: IntoIterator<Item = (K, V)>, K: AsRef<[u8]>, V: AsRef<[u8]>
+{
+	let mut memdb = MemoryDB::<KeccakHasher>::new();
+	let mut root = KECCAK_NULL_RLP;
+	{
+		let mut trie = TrieFactory::new(spec).create(&mut memdb, &mut root);
+		for (key, value) in items {
+			trie.insert(key.as_ref(), value.as_ref()).expect("MemoryDB never fails; qed");
+		}
+	}
+	(memdb, root)
+}
@@ -0,0 +1,109 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Consistency checking of Keccak/Rlp-flavoured tries.
+
+use ethereum_types::H256;
+use trie::{Trie, TrieError};
+
+use iterator::{Walker, Direction};
+use TrieDB;
+
+/// Number of nodes checked between two calls to the progress callback.
+const PROGRESS_INTERVAL: usize = 100_000;
+
+/// Check that every node reachable from the root of `trie` is present in the database and
+/// decodes, returning the hashes of the missing or malformed nodes otherwise.
+///
+/// A malformed node inlined into its parent is reported under the hash of the parent. Nodes below
+/// a missing or malformed node can't be reached and are not checked.
+pub fn validate<'db>(trie: &'db TrieDB<'db>) -> Result<(), Vec<H256>> {
+	validate_with_progress(trie, |_| {})
+}
+
+/// Like `validate`, but calls `progress` with the number of nodes checked so far at regular
+/// intervals, and once more with the total when done.
+pub fn validate_with_progress<'db, F>(trie: &'db TrieDB<'db>, mut progress: F) -> Result<(), Vec<H256>>
+	where F: FnMut(usize)
+{
+	let mut bad = Vec::new();
+	let mut checked = 0;
+	for node in Walker::new(trie.db(), trie.root(), Vec::new(), Direction::Forward) {
+		if let Err(e) = node {
+			match *e {
+				TrieError::InvalidStateRoot(hash) |
+				TrieError::IncompleteDatabase(hash) |
+				TrieError::DecoderError(hash, _) => bad.push(hash),
+			}
+		}
+
+		checked += 1;
+		if checked % PROGRESS_INTERVAL == 0 {
+			progress(checked);
+		}
+	}
+	progress(checked);
+
+	match bad.is_empty() {
+		true => Ok(()),
+		false => Err(bad),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethereum_types::H256;
+	use hashdb::{HashDB, DBValue};
+	use keccak_hash::{keccak, KECCAK_NULL_RLP};
+	use keccak_hasher::KeccakHasher;
+	use memorydb::MemoryDB;
+	use test_helpers::sample_trie;
+	use {prove, TrieDB};
+	use super::{validate, validate_with_progress};
+
+	fn leaf_hash(memdb: &MemoryDB<KeccakHasher>, root: &H256, key: &[u8]) -> H256 {
+		let t = TrieDB::new(memdb, root).unwrap();
+		keccak(prove(&t, key).unwrap().last().unwrap())
+	}
+
+	#[test]
+	fn intact_trie_is_valid() {
+		let (memdb, root) = sample_trie();
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(validate(&t), Ok(()));
+
+		let mut last = 0;
+		validate_with_progress(&t, |checked| last = checked).unwrap();
+		assert!(last > 100);
+
+		let empty = KECCAK_NULL_RLP;
+		assert_eq!(validate(&TrieDB::new(&memdb, &empty).unwrap()), Ok(()));
+	}
+
+	#[test]
+	fn reports_missing_and_malformed_nodes() {
+		let (mut memdb, root) = sample_trie();
+		let missing = leaf_hash(&memdb, &root, &[7, 7]);
+		let malformed = leaf_hash(&memdb, &root, &[42, 42]);
+
+		memdb.remove(&missing);
+		memdb.remove(&malformed);
+		memdb.emplace(malformed, DBValue::from_slice(&[0xc3, 0x01, 0x02, 0x03]));
+
+		let t = TrieDB::new(&memdb, &root).unwrap();
+		assert_eq!(validate(&t), Err(vec![missing, malformed]));
+	}
+}
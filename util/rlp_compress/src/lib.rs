@@ -16,7 +16,7 @@ mod common;
 use std::cmp;
 use std::collections::HashMap;
 use elastic_array::ElasticArray1024;
-use rlp::{Rlp, RlpStream};
+use rlp::{Rlp, RlpStream, PayloadInfo};
 use common::{SNAPSHOT_SWAPPER, BLOCKS_SWAPPER};
 
 pub fn snapshot_swapper() -> &'static Swapper<'static> {
@@ -59,6 +59,130 @@ pub fn decompress(c: &[u8], swapper: &Decompressor) -> ElasticArray1024<u8> {
 	}
 }
 
+/// Like `compress`, but also descends into data items whose payload is a single rlp list,
+/// such as account rlp stored as the value of a trie leaf.
+pub fn compress_nested(c: &[u8], swapper: &Compressor) -> ElasticArray1024<u8> {
+	let rlp = Rlp::new(c);
+	if rlp.is_data() {
+		if let Some(compressed) = swapper.compressed(rlp.as_raw()) {
+			return ElasticArray1024::from_slice(compressed);
+		}
+		match nested_list(&rlp) {
+			Some(nested) => rlp::encode(&compress_nested(nested, swapper).to_vec()),
+			None => ElasticArray1024::from_slice(rlp.as_raw()),
+		}
+	} else {
+		map_rlp(&rlp, |r| compress_nested(r.as_raw(), swapper))
+	}
+}
+
+/// Reverse of `compress_nested`.
+pub fn decompress_nested(c: &[u8], swapper: &Decompressor) -> ElasticArray1024<u8> {
+	let rlp = Rlp::new(c);
+	if rlp.is_data() {
+		if let Some(decompressed) = swapper.decompressed(rlp.as_raw()) {
+			return ElasticArray1024::from_slice(decompressed);
+		}
+		match nested_list(&rlp) {
+			Some(nested) => rlp::encode(&decompress_nested(nested, swapper).to_vec()),
+			None => ElasticArray1024::from_slice(rlp.as_raw()),
+		}
+	} else {
+		map_rlp(&rlp, |r| decompress_nested(r.as_raw(), swapper))
+	}
+}
+
+/// Payload of a data item, if it consists of exactly one well-formed rlp list.
+///
+/// Descending re-encodes the headers of the item and of every list below it, so all of them
+/// must already be canonical. Compression keeps them so, which makes both directions agree on
+/// which items to descend into.
+fn nested_list<'a>(rlp: &Rlp<'a>) -> Option<&'a [u8]> {
+	let payload = rlp.data().ok()?;
+	let nested = Rlp::new(payload);
+	match rlp.payload_info().map_or(false, |info| is_canonical_header(&info))
+		&& nested.is_list() && is_well_formed(&nested) {
+		true => Some(payload),
+		false => None,
+	}
+}
+
+/// Checks the structure of `rlp`; lists must have canonical headers, data items need not.
+fn is_well_formed(rlp: &Rlp) -> bool {
+	let info = match rlp.payload_info() {
+		Ok(info) => info,
+		Err(_) => return false,
+	};
+	if info.header_len + info.value_len != rlp.as_raw().len() {
+		return false;
+	}
+	if !rlp.is_list() {
+		return true;
+	}
+	if !is_canonical_header(&info) {
+		return false;
+	}
+
+	let mut items_len = 0;
+	for item in rlp.iter() {
+		if !is_well_formed(&item) {
+			return false;
+		}
+		items_len += item.as_raw().len();
+	}
+	items_len == info.value_len
+}
+
+/// Checks that `c` is exactly one rlp item with canonical headers, including the items nested
+/// in data payloads. `decompress_nested` restores such input from `compress_nested` output, as
+/// the swapped tokens are non-canonical and can't be confused with an original item.
+pub fn is_canonical(c: &[u8]) -> bool {
+	is_canonical_item(&Rlp::new(c))
+}
+
+fn is_canonical_item(rlp: &Rlp) -> bool {
+	let info = match rlp.payload_info() {
+		Ok(info) => info,
+		Err(_) => return false,
+	};
+	if info.header_len + info.value_len != rlp.as_raw().len() {
+		return false;
+	}
+	// a single byte below 0x80 is its own encoding.
+	if info.header_len == 0 {
+		return true;
+	}
+	if !is_canonical_header(&info) {
+		return false;
+	}
+
+	if rlp.is_data() {
+		let payload = &rlp.as_raw()[info.header_len..];
+		if info.value_len == 1 && payload[0] < 0x80 {
+			return false;
+		}
+		let nested = Rlp::new(payload);
+		return !(nested.is_list() && is_well_formed(&nested)) || is_canonical_item(&nested);
+	}
+
+	let mut items_len = 0;
+	for item in rlp.iter() {
+		if !is_canonical_item(&item) {
+			return false;
+		}
+		items_len += item.as_raw().len();
+	}
+	items_len == info.value_len
+}
+
+fn is_canonical_header(info: &PayloadInfo) -> bool {
+	let len_of_len = match info.value_len {
+		0...55 => 0,
+		len => (64 - (len as u64).leading_zeros() as usize + 7) / 8,
+	};
+	info.header_len == 1 + len_of_len
+}
+
 fn map_rlp<F: Fn(&Rlp) -> ElasticArray1024<u8>>(rlp: &Rlp, f: F) -> ElasticArray1024<u8> {
 	let mut stream = RlpStream::new_list(rlp.item_count().unwrap_or_default());
 	for subrlp in rlp.iter() {
@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+extern crate rlp;
 extern crate rlp_compress;
 
-use rlp_compress::{compress, decompress, Swapper, snapshot_swapper, blocks_swapper, Compressor, Decompressor};
+use rlp::RlpStream;
+use rlp_compress::{compress, decompress, compress_nested, decompress_nested, is_canonical, Swapper, snapshot_swapper, blocks_swapper, Compressor, Decompressor};
 
 #[test]
 fn invalid_rlp_swapper() {
@@ -64,3 +66,92 @@ fn large_block() {
 	let decompressed = decompress(&compressed, blocks_swapper());
 	assert_eq!(decompressed.into_vec(), block);
 }
+
+#[test]
+fn nested_account_in_leaf() {
+	let basic_account_rlp = vec![248, 68, 4, 2, 160, 86, 232, 31, 23, 27, 204, 85, 166, 255, 131, 69, 230, 146, 192, 248, 110, 91, 72, 224, 27, 153, 108, 173, 192, 1, 98, 47, 181, 227, 99, 180, 33, 160, 197, 210, 70, 1, 134, 247, 35, 60, 146, 126, 125, 178, 220, 199, 3, 192, 229, 0, 182, 83, 202, 130, 39, 59, 123, 250, 216, 4, 93, 133, 164, 112];
+	let mut leaf = RlpStream::new_list(2);
+	leaf.append(&vec![0x20u8, 1, 2]);
+	leaf.append(&basic_account_rlp);
+	let leaf = leaf.out();
+
+	// the plain compressor doesn't look inside the leaf value
+	assert_eq!(compress(&leaf, snapshot_swapper()).into_vec(), leaf);
+
+	let compressed = compress_nested(&leaf, snapshot_swapper());
+	assert_eq!(compressed.to_vec(), vec![204, 131, 0x20, 1, 2, 135, 198, 4, 2, 129, 0, 129, 1]);
+	assert_eq!(decompress_nested(&compressed, snapshot_swapper()).into_vec(), leaf);
+}
+
+#[test]
+fn nested_round_trip() {
+	struct Lcg(u64);
+
+	impl Lcg {
+		fn next(&mut self, bound: u64) -> u64 {
+			self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			(self.0 >> 33) % bound
+		}
+	}
+
+	let hashes: Vec<Vec<u8>> = vec![
+		vec![86, 232, 31, 23, 27, 204, 85, 166, 255, 131, 69, 230, 146, 192, 248, 110, 91, 72, 224, 27, 153, 108, 173, 192, 1, 98, 47, 181, 227, 99, 180, 33],
+		vec![197, 210, 70, 1, 134, 247, 35, 60, 146, 126, 125, 178, 220, 199, 3, 192, 229, 0, 182, 83, 202, 130, 39, 59, 123, 250, 216, 4, 93, 133, 164, 112],
+	];
+
+	fn item(rng: &mut Lcg, hashes: &[Vec<u8>], depth: u32) -> Vec<u8> {
+		match rng.next(if depth == 0 { 3 } else { 5 }) {
+			0 => rlp::encode(&hashes[rng.next(2) as usize]).into_vec(),
+			1 => {
+				let data: Vec<u8> = (0..rng.next(70)).map(|_| rng.next(256) as u8).collect();
+				rlp::encode(&data).into_vec()
+			},
+			2 => rlp::encode(&rng.next(1 << 20)).into_vec(),
+			3 => {
+				let nested = item(rng, hashes, depth - 1);
+				rlp::encode(&nested).into_vec()
+			},
+			_ => {
+				let count = rng.next(17) as usize;
+				let mut stream = RlpStream::new_list(count);
+				for _ in 0..count {
+					stream.append_raw(&item(rng, hashes, depth - 1), 1);
+				}
+				stream.out()
+			},
+		}
+	}
+
+	let mut rng = Lcg(42);
+	for _ in 0..1000 {
+		let rlp = item(&mut rng, &hashes, 4);
+		let compressed = compress_nested(&rlp, snapshot_swapper());
+		assert!(compressed.len() <= rlp.len());
+		assert_eq!(decompress_nested(&compressed, snapshot_swapper()).into_vec(), rlp);
+	}
+}
+
+#[test]
+fn canonical_rlp() {
+	assert!(is_canonical(&[0x05]));
+	assert!(is_canonical(&[0x80]));
+	assert!(is_canonical(&[0x81, 0x80]));
+	assert!(is_canonical(&[0xc0]));
+	assert!(is_canonical(&[0xc4, 0x01, 0x82, 0x01, 0x02]));
+	// a list holding a data item which holds a list.
+	assert!(is_canonical(&[0xc4, 0x20, 0x82, 0xc1, 0x01]));
+
+	// single bytes below 0x80 encoded as strings, which includes the swapped tokens.
+	assert!(!is_canonical(&[0x81, 0x00]));
+	assert!(!is_canonical(&[0x81, 0x7f]));
+	assert!(!is_canonical(&[0xc2, 0x81, 0x01]));
+	// long length prefixes for short payloads.
+	assert!(!is_canonical(&[0xb8, 0x01, 0x80]));
+	assert!(!is_canonical(&[0xf8, 0x02, 0x01, 0x02]));
+	// a non-canonical item inside a list nested in a data item.
+	assert!(!is_canonical(&[0x84, 0xc3, 0x01, 0x81, 0x01]));
+	// trailing bytes, truncated items and nothing at all.
+	assert!(!is_canonical(&[0x01, 0x02]));
+	assert!(!is_canonical(&[0x82, 0x01]));
+	assert!(!is_canonical(&[]));
+}